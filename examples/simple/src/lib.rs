@@ -4,12 +4,12 @@ use wasm_bindgen::prelude::*;
 use std::rc::Rc;
 use std::cell::RefCell;
 use dommer::node::Node;
-use dommer::event::{SubscribeGuard, EventKind};
+use dommer::event::{Scope, EventKind};
 
 #[wasm_bindgen]
 pub struct App {
     count: Rc<RefCell<u32>>,
-    guards: Vec<SubscribeGuard>,
+    scope: Scope,
 }
 
 #[wasm_bindgen]
@@ -19,7 +19,7 @@ impl App {
     #[wasm_bindgen]
     pub extern fn shutdown(self) {
         dommer::println!("At end, count was {}", self.count.borrow());
-        let _guards = self.guards;
+        let _scope = self.scope;
     }
 }
 
@@ -42,10 +42,10 @@ pub extern fn run() -> App {
     container.append_child(&increment_btn);
     document.body().append_child(&container);
 
-    let mut guards = Vec::new();
+    let scope = Scope::new();
     let dec_count = count.clone();
     let dec_count_text = count_text.clone();
-    guards.push(decrement_btn.add_event_listener(EventKind::Click, move |_| {
+    scope.add(decrement_btn.add_event_listener(EventKind::Click, move |_| {
         if *dec_count.borrow() == 0 {
             return;
         }
@@ -54,7 +54,7 @@ pub extern fn run() -> App {
     }));
     let inc_count = count.clone();
     let inc_count_text = count_text.clone();
-    guards.push(increment_btn.add_event_listener(EventKind::Click, move |_| {
+    scope.add(increment_btn.add_event_listener(EventKind::Click, move |_| {
         use std::u32;
         if *inc_count.borrow() == u32::MAX {
             return;
@@ -64,7 +64,7 @@ pub extern fn run() -> App {
     }));
     App {
         count,
-        guards,
+        scope,
     }
 }
 
@@ -0,0 +1,110 @@
+//! Cancel asynchronous operations, using the browser's `AbortController`.
+
+use std::fmt;
+
+use crate::{
+    backend::web::WebHandle,
+    event::{EventKind, EventTarget, SubscribeGuard},
+};
+
+/// Aborts everything watching its `AbortSignal`, either when `abort` is called or when the handle
+/// is dropped.
+pub struct AbortHandle {
+    controller: Option<web_sys::AbortController>,
+}
+
+impl AbortHandle {
+    /// Create a handle, and the signal it controls.
+    pub fn new() -> (AbortHandle, AbortSignal) {
+        let controller = expect!(
+            web_sys::AbortController::new(),
+            "creating an `AbortController`"
+        );
+        let signal = AbortSignal::from_web_sys(controller.signal());
+        let handle = AbortHandle {
+            controller: Some(controller),
+        };
+        (handle, signal)
+    }
+
+    pub fn abort(&self) {
+        if let Some(controller) = &self.controller {
+            controller.abort();
+        }
+    }
+
+    /// Another copy of the signal this handle controls.
+    pub fn signal(&self) -> AbortSignal {
+        let controller = expect_opt!(self.controller.as_ref(), "AbortHandle was forgotten");
+        AbortSignal::from_web_sys(controller.signal())
+    }
+
+    /// Drop the handle without aborting.
+    pub fn forget(mut self) {
+        self.controller = None;
+    }
+
+    /// A function that aborts, for callbacks that can't own the handle.
+    pub(crate) fn abort_fn(&self) -> impl Fn() + 'static {
+        let controller = self.controller.clone();
+        move || {
+            if let Some(controller) = &controller {
+                controller.abort();
+            }
+        }
+    }
+
+    /// Abort when `signal` is aborted, for as long as the returned guard is alive.
+    pub(crate) fn follow(&self, signal: &AbortSignal) -> Option<SubscribeGuard> {
+        let controller = self.controller.clone()?;
+        if signal.aborted() {
+            controller.abort();
+            None
+        } else {
+            Some(signal.add_event_listener(EventKind::Abort, move |_| controller.abort()))
+        }
+    }
+}
+
+impl fmt::Debug for AbortHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("AbortHandle")
+    }
+}
+
+impl Drop for AbortHandle {
+    fn drop(&mut self) {
+        self.abort();
+    }
+}
+
+/// Tells an operation that it should stop. Fires an `abort` event when it is aborted.
+#[derive(Debug, Clone)]
+pub struct AbortSignal {
+    inner: web_sys::AbortSignal,
+    target: EventTarget,
+}
+
+impl std::ops::Deref for AbortSignal {
+    type Target = EventTarget;
+
+    fn deref(&self) -> &Self::Target {
+        &self.target
+    }
+}
+
+impl AbortSignal {
+    fn from_web_sys(inner: web_sys::AbortSignal) -> Self {
+        let target = EventTarget::from_handle(WebHandle::wrap(inner.clone()));
+        AbortSignal { inner, target }
+    }
+
+    pub fn aborted(&self) -> bool {
+        self.inner.aborted()
+    }
+
+    /// The underlying `web_sys` signal.
+    pub fn as_web_sys(&self) -> &web_sys::AbortSignal {
+        &self.inner
+    }
+}
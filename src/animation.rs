@@ -0,0 +1,220 @@
+//! Running code before the browser paints, with `requestAnimationFrame`.
+//!
+//! Like `window`, this only works on the web.
+
+use futures::channel::oneshot;
+use std::{
+    cell::{Cell, RefCell},
+    fmt,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+use wasm_bindgen::{closure::Closure, JsCast};
+
+use crate::event::SubscribeGuard;
+
+/// Call `callback` with the frame's timestamp (in milliseconds, like `Event::time_stamp`) before
+/// the next repaint. Dropping the guard first cancels it.
+pub fn animation_frame(callback: impl FnOnce(f64) + 'static) -> SubscribeGuard {
+    let window = crate::window();
+    let closure = Closure::once(callback);
+    let id = expect!(
+        window
+            .as_web_sys()
+            .request_animation_frame(closure.as_ref().unchecked_ref()),
+        "calling Window::request_animation_frame"
+    );
+    let mut closure = Some(closure);
+    SubscribeGuard::new(move || {
+        // Cancelling a frame that has already run does nothing.
+        let _ = window.as_web_sys().cancel_animation_frame(id);
+        closure.take();
+    })
+}
+
+/// A future giving the timestamp of the next frame, see `animation_frame`.
+pub fn next_frame() -> NextFrame {
+    let (sender, receiver) = oneshot::channel();
+    let guard = animation_frame(move |timestamp| {
+        let _ = sender.send(timestamp);
+    });
+    NextFrame {
+        receiver,
+        _guard: guard,
+    }
+}
+
+/// The timestamp of the next frame, from `next_frame`.
+///
+/// Dropping the future before the frame cancels it.
+#[derive(Debug)]
+pub struct NextFrame {
+    receiver: oneshot::Receiver<f64>,
+    _guard: SubscribeGuard,
+}
+
+impl Future for NextFrame {
+    type Output = f64;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<f64> {
+        match Pin::new(&mut self.receiver).poll(cx) {
+            Poll::Ready(Ok(timestamp)) => Poll::Ready(timestamp),
+            // The callback is kept until the frame runs, so the sender can't be dropped first.
+            Poll::Ready(Err(_)) | Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// What an `AnimationLoop` callback is told about the current frame.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Frame {
+    /// When the frame started, in milliseconds.
+    pub timestamp: f64,
+    /// Milliseconds since the previous frame, or 0 for the first one.
+    pub delta: f64,
+    /// How many frames were missed since the previous one, judging by the shortest gap between
+    /// frames seen so far.
+    pub dropped_frames: u32,
+}
+
+/// Works out the timing of each frame from its timestamp.
+#[derive(Debug, Default)]
+struct FrameClock {
+    last: Option<f64>,
+    // The shortest gap between frames, which is taken to be the refresh interval.
+    interval: Option<f64>,
+    dropped_frames: u64,
+}
+
+impl FrameClock {
+    fn tick(&mut self, timestamp: f64) -> Frame {
+        let delta = self.last.map_or(0., |last| timestamp - last);
+        self.last = Some(timestamp);
+        if delta <= 0. {
+            return Frame {
+                timestamp,
+                delta,
+                dropped_frames: 0,
+            };
+        }
+        let interval = match self.interval {
+            Some(interval) if interval <= delta => interval,
+            _ => delta,
+        };
+        self.interval = Some(interval);
+        // Allow for some jitter before counting a frame as dropped.
+        let dropped_frames = ((delta / interval) + 0.5).floor().max(1.) as u32 - 1;
+        self.dropped_frames += u64::from(dropped_frames);
+        Frame {
+            timestamp,
+            delta,
+            dropped_frames,
+        }
+    }
+}
+
+type FrameClosure = Closure<dyn FnMut(f64)>;
+
+struct Shared {
+    callback: RefCell<Box<dyn FnMut(Frame)>>,
+    clock: RefCell<FrameClock>,
+    id: Cell<Option<i32>>,
+    closure: RefCell<Option<FrameClosure>>,
+}
+
+impl Shared {
+    fn request(&self) {
+        let closure = self.closure.borrow();
+        if let Some(closure) = &*closure {
+            let id = expect!(
+                crate::window()
+                    .as_web_sys()
+                    .request_animation_frame(closure.as_ref().unchecked_ref()),
+                "calling Window::request_animation_frame"
+            );
+            self.id.set(Some(id));
+        }
+    }
+}
+
+/// Calls a function on every frame until it is dropped.
+pub struct AnimationLoop {
+    shared: Rc<Shared>,
+}
+
+impl AnimationLoop {
+    /// Start calling `callback` before each repaint, beginning with the next one.
+    pub fn new(callback: impl FnMut(Frame) + 'static) -> AnimationLoop {
+        let shared = Rc::new(Shared {
+            callback: RefCell::new(Box::new(callback)),
+            clock: RefCell::new(FrameClock::default()),
+            id: Cell::new(None),
+            closure: RefCell::new(None),
+        });
+        let weak = Rc::downgrade(&shared);
+        let closure = Closure::wrap(Box::new(move |timestamp: f64| {
+            let shared = match weak.upgrade() {
+                Some(shared) => shared,
+                None => return,
+            };
+            shared.id.set(None);
+            let frame = shared.clock.borrow_mut().tick(timestamp);
+            (shared.callback.borrow_mut())(frame);
+            // Does nothing if the callback stopped the loop.
+            shared.request();
+        }) as Box<dyn FnMut(f64)>);
+        *shared.closure.borrow_mut() = Some(closure);
+        shared.request();
+        AnimationLoop { shared }
+    }
+
+    /// The number of frames missed since the loop started.
+    pub fn dropped_frames(&self) -> u64 {
+        self.shared.clock.borrow().dropped_frames
+    }
+
+    /// Stop the loop. The same as dropping it.
+    pub fn stop(self) {}
+}
+
+impl fmt::Debug for AnimationLoop {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AnimationLoop")
+            .field("dropped_frames", &self.dropped_frames())
+            .finish()
+    }
+}
+
+impl Drop for AnimationLoop {
+    fn drop(&mut self) {
+        if let Some(id) = self.shared.id.take() {
+            let _ = crate::window().as_web_sys().cancel_animation_frame(id);
+        }
+        let closure = self.shared.closure.borrow_mut().take();
+        drop(closure);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_clock() {
+        let mut clock = FrameClock::default();
+        assert_eq!(clock.tick(100.).delta, 0.);
+        let frame = clock.tick(116.);
+        assert_eq!((frame.delta, frame.dropped_frames), (16., 0));
+        // Jitter isn't a dropped frame.
+        assert_eq!(clock.tick(135.).dropped_frames, 0);
+        // Two frames were missed.
+        let frame = clock.tick(183.);
+        assert_eq!((frame.delta, frame.dropped_frames), (48., 2));
+        // A faster refresh rate is picked up.
+        assert_eq!(clock.tick(191.).dropped_frames, 0);
+        assert_eq!(clock.tick(215.).dropped_frames, 2);
+        assert_eq!(clock.dropped_frames, 4);
+    }
+}
@@ -0,0 +1,394 @@
+//! Just enough html parsing and serialization for `inner_html` and friends.
+//!
+//! The parser is forgiving in the same spirit as the browser's, but doesn't implement the full
+//! tree construction algorithm (e.g. it won't move a `<tr>` into a `<tbody>`).
+
+/// A node parsed from html, before it is turned into a `MemoryNode`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(super) enum Parsed {
+    Element {
+        name: String,
+        attributes: Vec<(String, String)>,
+        children: Vec<Parsed>,
+    },
+    Text(String),
+    Comment(String),
+}
+
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+const RAW_TEXT_ELEMENTS: &[&str] = &["script", "style"];
+
+/// Elements that never have children or an end tag.
+pub(super) fn is_void(name: &str) -> bool {
+    VOID_ELEMENTS.contains(&name)
+}
+
+/// Elements whose content is not parsed or escaped.
+pub(super) fn is_raw_text(name: &str) -> bool {
+    RAW_TEXT_ELEMENTS.contains(&name)
+}
+
+pub(super) fn escape_text_into(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '\u{a0}' => out.push_str("&nbsp;"),
+            c => out.push(c),
+        }
+    }
+}
+
+pub(super) fn escape_attribute_into(value: &str, out: &mut String) {
+    for c in value.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '"' => out.push_str("&quot;"),
+            '\u{a0}' => out.push_str("&nbsp;"),
+            c => out.push(c),
+        }
+    }
+}
+
+/// Replace character references with the characters they stand for. Unknown references are left
+/// alone.
+fn decode_entities(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').and_then(|end| {
+            let name = &rest[1..end];
+            let c = match name {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some('\u{a0}'),
+                _ if name.starts_with("#x") || name.starts_with("#X") => {
+                    u32::from_str_radix(&name[2..], 16)
+                        .ok()
+                        .and_then(std::char::from_u32)
+                }
+                _ if name.starts_with('#') => {
+                    name[1..].parse::<u32>().ok().and_then(std::char::from_u32)
+                }
+                _ => None,
+            };
+            c.map(|c| (c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+struct OpenElement {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Parsed>,
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+    root: Vec<Parsed>,
+    open: Vec<OpenElement>,
+}
+
+/// Parse a fragment of html, as it would appear inside a `<div>`.
+pub(super) fn parse_fragment(input: &str) -> Vec<Parsed> {
+    let mut parser = Parser {
+        input,
+        pos: 0,
+        root: Vec::new(),
+        open: Vec::new(),
+    };
+    parser.parse();
+    parser.root
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn parse(&mut self) {
+        while !self.rest().is_empty() {
+            let rest = self.rest();
+            if rest.starts_with("<!--") {
+                let (comment, len) = match rest[4..].find("-->") {
+                    Some(end) => (&rest[4..4 + end], 4 + end + 3),
+                    None => (&rest[4..], rest.len()),
+                };
+                self.push(Parsed::Comment(comment.to_owned()));
+                self.pos += len;
+            } else if rest.starts_with("</") {
+                // An end tag missing its '>' runs to the end of the input.
+                let (name, len) = match rest.find('>') {
+                    Some(end) => (&rest[2..end], end + 1),
+                    None => (&rest[2..], rest.len()),
+                };
+                let name = name.trim().to_ascii_lowercase();
+                self.pos += len;
+                self.close(&name);
+            } else if rest.starts_with("<!") || rest.starts_with("<?") {
+                // Doctypes and processing instructions are ignored.
+                self.pos += rest.find('>').map_or(rest.len(), |end| end + 1);
+            } else if rest.starts_with('<')
+                && rest[1..].starts_with(|c: char| c.is_ascii_alphabetic())
+            {
+                self.start_tag();
+            } else {
+                // Skip the first character so a stray '<' becomes text.
+                let first = rest.chars().next().map_or(0, char::len_utf8);
+                let end = rest[first..]
+                    .find('<')
+                    .map_or(rest.len(), |end| end + first);
+                self.push_text(&decode_entities(&rest[..end]));
+                self.pos += end;
+            }
+        }
+        while !self.open.is_empty() {
+            self.pop();
+        }
+    }
+
+    fn push(&mut self, node: Parsed) {
+        match self.open.last_mut() {
+            Some(parent) => parent.children.push(node),
+            None => self.root.push(node),
+        }
+    }
+
+    fn push_text(&mut self, text: &str) {
+        let siblings = match self.open.last_mut() {
+            Some(parent) => &mut parent.children,
+            None => &mut self.root,
+        };
+        match siblings.last_mut() {
+            Some(Parsed::Text(previous)) => previous.push_str(text),
+            _ => siblings.push(Parsed::Text(text.to_owned())),
+        }
+    }
+
+    fn pop(&mut self) {
+        if let Some(element) = self.open.pop() {
+            self.push(Parsed::Element {
+                name: element.name,
+                attributes: element.attributes,
+                children: element.children,
+            });
+        }
+    }
+
+    /// Handle an end tag. End tags that don't match an open element are ignored.
+    fn close(&mut self, name: &str) {
+        if let Some(idx) = self.open.iter().rposition(|element| element.name == name) {
+            while self.open.len() > idx {
+                self.pop();
+            }
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// Take characters up to (not including) the first one matching `stop`.
+    fn take_until(&mut self, stop: impl Fn(char) -> bool) -> &'a str {
+        let rest = self.rest();
+        let end = rest.find(stop).unwrap_or_else(|| rest.len());
+        self.pos += end;
+        &rest[..end]
+    }
+
+    fn start_tag(&mut self) {
+        self.pos += 1;
+        let name = self
+            .take_until(|c| c.is_whitespace() || c == '/' || c == '>')
+            .to_ascii_lowercase();
+        let mut attributes: Vec<(String, String)> = Vec::new();
+        let mut self_closing = false;
+        loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            if rest.is_empty() {
+                break;
+            } else if rest.starts_with('>') {
+                self.pos += 1;
+                break;
+            } else if rest.starts_with("/>") {
+                self.pos += 2;
+                self_closing = true;
+                break;
+            } else if rest.starts_with('/') {
+                self.pos += 1;
+                continue;
+            }
+            let attr_name = self
+                .take_until(|c| c.is_whitespace() || c == '=' || c == '>' || c == '/')
+                .to_ascii_lowercase();
+            self.skip_whitespace();
+            let mut value = String::new();
+            if self.rest().starts_with('=') {
+                self.pos += 1;
+                self.skip_whitespace();
+                let rest = self.rest();
+                value = match rest.chars().next() {
+                    Some(quote) if quote == '"' || quote == '\'' => {
+                        self.pos += 1;
+                        let raw = self.take_until(|c| c == quote);
+                        if !self.rest().is_empty() {
+                            self.pos += 1;
+                        }
+                        decode_entities(raw)
+                    }
+                    _ => decode_entities(self.take_until(|c| c.is_whitespace() || c == '>')),
+                };
+            }
+            if !attributes
+                .iter()
+                .any(|(existing, _)| *existing == attr_name)
+            {
+                attributes.push((attr_name, value));
+            }
+        }
+        if is_void(&name) || self_closing {
+            self.push(Parsed::Element {
+                name,
+                attributes,
+                children: Vec::new(),
+            });
+        } else if is_raw_text(&name) {
+            let rest = self.rest();
+            let end_tag = format!("</{}", name);
+            let end = rest
+                .to_ascii_lowercase()
+                .find(&end_tag)
+                .unwrap_or_else(|| rest.len());
+            let children = if end > 0 {
+                vec![Parsed::Text(rest[..end].to_owned())]
+            } else {
+                Vec::new()
+            };
+            self.pos += end;
+            self.pos += self
+                .rest()
+                .find('>')
+                .map_or(self.rest().len(), |end| end + 1);
+            self.push(Parsed::Element {
+                name,
+                attributes,
+                children,
+            });
+        } else {
+            self.open.push(OpenElement {
+                name,
+                attributes,
+                children: Vec::new(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn element(name: &str, attributes: &[(&str, &str)], children: Vec<Parsed>) -> Parsed {
+        Parsed::Element {
+            name: name.to_owned(),
+            attributes: attributes
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            children,
+        }
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            parse_fragment(r#"<P Class=x id="a &amp; b">1 &lt; 2<br>3</p><img src='y'/>"#),
+            vec![
+                element(
+                    "p",
+                    &[("class", "x"), ("id", "a & b")],
+                    vec![
+                        Parsed::Text("1 < 2".to_owned()),
+                        element("br", &[], vec![]),
+                        Parsed::Text("3".to_owned()),
+                    ]
+                ),
+                element("img", &[("src", "y")], vec![]),
+            ]
+        );
+        assert_eq!(
+            parse_fragment("<script>if (a < b) {}</script>a < b<div>"),
+            vec![
+                element(
+                    "script",
+                    &[],
+                    vec![Parsed::Text("if (a < b) {}".to_owned())]
+                ),
+                Parsed::Text("a < b".to_owned()),
+                element("div", &[], vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_non_ascii() {
+        assert_eq!(
+            parse_fragment("héllo"),
+            vec![Parsed::Text("héllo".to_owned())]
+        );
+        assert_eq!(
+            parse_fragment("<b>日本</b>語"),
+            vec![
+                element("b", &[], vec![Parsed::Text("日本".to_owned())]),
+                Parsed::Text("語".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_truncated() {
+        assert_eq!(parse_fragment("a</"), vec![Parsed::Text("a".to_owned())]);
+        assert_eq!(parse_fragment("</é"), vec![]);
+        assert_eq!(
+            parse_fragment("<b>x</b"),
+            vec![element("b", &[], vec![Parsed::Text("x".to_owned())])]
+        );
+        for input in &[
+            "<",
+            "<b",
+            "<b a",
+            "<b a=",
+            "<b a='x",
+            "<!--",
+            "<!",
+            "<script>x</scr",
+        ] {
+            parse_fragment(input);
+        }
+    }
+}
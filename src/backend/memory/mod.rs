@@ -0,0 +1,1649 @@
+//! A DOM implemented in pure rust, for running code (and tests) outside of a browser.
+//!
+//! It supports tree mutation, attributes, text content, css selectors, reading and writing html,
+//! and event dispatch with capture and bubble phases. There is no layout, so the geometry methods
+//! use the defaults from `Handle`.
+
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    fmt,
+    rc::{Rc, Weak},
+};
+
+use self::{html::Parsed, selector::SelectorList};
+use super::{Backend, EventHandle, Handle, Listener, Unsubscribe};
+use crate::{
+    element::InsertPosition,
+    error::{DomException, DomExceptionKind},
+    event::{AddEventListenerOptions, Event, EventData, EventInit, EventPhase},
+    node::{DocumentPosition, NodeType},
+};
+
+mod html;
+mod selector;
+
+const HTML_NAMESPACE: &str = "http://www.w3.org/1999/xhtml";
+const XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+const XMLNS_NAMESPACE: &str = "http://www.w3.org/2000/xmlns/";
+
+thread_local! {
+    static DOCUMENT: Rc<MemoryNode> = MemoryNode::html_document();
+}
+
+/// The in-memory backend.
+///
+/// Each thread gets its own document, which starts out as
+/// `<html><head></head><body></body></html>`.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct MemoryBackend;
+
+impl MemoryBackend {
+    /// Create a new document, separate from the one returned by `Backend::document`.
+    pub fn new_document(&self) -> Rc<dyn Handle> {
+        MemoryNode::html_document()
+    }
+}
+
+impl Backend for MemoryBackend {
+    fn document(&self) -> Option<Rc<dyn Handle>> {
+        Some(DOCUMENT.with(|document| document.clone()))
+    }
+
+    fn create_event_target(&self) -> Result<Rc<dyn Handle>, DomException> {
+        Ok(MemoryNode::new(Kind::Target))
+    }
+
+    fn create_event(
+        &self,
+        kind: &str,
+        init: &EventInit,
+        data: &EventData,
+    ) -> Result<Rc<dyn EventHandle>, DomException> {
+        Ok(Rc::new(MemoryEvent::new(
+            kind.to_owned(),
+            init.bubbles.unwrap_or(false),
+            init.cancelable.unwrap_or(false),
+            init.composed.unwrap_or(false),
+            data.clone(),
+        )))
+    }
+}
+
+fn hierarchy_request_error(message: &str) -> DomException {
+    DomException::new(DomExceptionKind::HierarchyRequest, message)
+}
+
+fn not_a_child() -> DomException {
+    DomException::new(
+        DomExceptionKind::NotFound,
+        "the node is not a child of this node",
+    )
+}
+
+/// Whether `name` is a valid xml name. We are a bit more lenient than the spec with non-ascii
+/// characters.
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' || c == ':' || !c.is_ascii() => (),
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || "-._:".contains(c) || !c.is_ascii())
+}
+
+fn validate_name(name: &str) -> Result<(), DomException> {
+    if is_valid_name(name) {
+        Ok(())
+    } else {
+        Err(DomException::new(
+            DomExceptionKind::InvalidCharacter,
+            format!("'{}' is not a valid name", name),
+        ))
+    }
+}
+
+fn same(a: &MemoryNode, b: &MemoryNode) -> bool {
+    std::ptr::eq(a, b)
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct Attribute {
+    namespace: Option<String>,
+    prefix: Option<String>,
+    local_name: String,
+    value: String,
+}
+
+impl Attribute {
+    fn qualified_name(&self) -> String {
+        match &self.prefix {
+            Some(prefix) => format!("{}:{}", prefix, self.local_name),
+            None => self.local_name.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ElementData {
+    namespace: Option<String>,
+    prefix: Option<String>,
+    local_name: String,
+    attributes: Vec<Attribute>,
+}
+
+impl ElementData {
+    fn is_html(&self) -> bool {
+        self.namespace.as_deref() == Some(HTML_NAMESPACE)
+    }
+
+    fn qualified_name(&self) -> String {
+        match &self.prefix {
+            Some(prefix) => format!("{}:{}", prefix, self.local_name),
+            None => self.local_name.clone(),
+        }
+    }
+
+    /// Attribute names are case-insensitive on html elements.
+    fn normalize_name(&self, name: &str) -> String {
+        if self.is_html() {
+            name.to_ascii_lowercase()
+        } else {
+            name.to_owned()
+        }
+    }
+
+    fn find(&self, name: &str) -> Option<usize> {
+        let name = self.normalize_name(name);
+        self.attributes
+            .iter()
+            .position(|attr| attr.qualified_name() == name)
+    }
+
+    fn find_ns(&self, namespace: Option<&str>, local_name: &str) -> Option<usize> {
+        self.attributes.iter().position(|attr| {
+            attr.namespace.as_deref() == namespace && attr.local_name == local_name
+        })
+    }
+
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.find(name)
+            .map(|idx| self.attributes[idx].value.as_str())
+    }
+
+    fn same_attributes(&self, other: &ElementData) -> bool {
+        self.attributes.len() == other.attributes.len()
+            && self.attributes.iter().all(|attr| {
+                other.attributes.iter().any(|other| {
+                    other.namespace == attr.namespace
+                        && other.local_name == attr.local_name
+                        && other.value == attr.value
+                })
+            })
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Kind {
+    /// An event target that isn't a node (`new EventTarget()`).
+    Target,
+    Document,
+    Element(ElementData),
+    Text(String),
+    Comment(String),
+}
+
+struct Registered {
+    kind: String,
+    capture: bool,
+    once: bool,
+    passive: bool,
+    callback: Listener,
+    removed: Cell<bool>,
+}
+
+struct Data {
+    kind: Kind,
+    parent: Weak<MemoryNode>,
+    children: Vec<Rc<MemoryNode>>,
+    listeners: Vec<Rc<Registered>>,
+}
+
+/// A node (or plain event target) in the in-memory DOM.
+pub struct MemoryNode {
+    // Set once, straight after the node is put in its `Rc`.
+    this: RefCell<Weak<MemoryNode>>,
+    data: RefCell<Data>,
+}
+
+impl fmt::Debug for MemoryNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MemoryNode")
+            .field("name", &self.node_name())
+            .finish()
+    }
+}
+
+impl MemoryNode {
+    fn new(kind: Kind) -> Rc<MemoryNode> {
+        let node = Rc::new(MemoryNode {
+            this: RefCell::new(Weak::new()),
+            data: RefCell::new(Data {
+                kind,
+                parent: Weak::new(),
+                children: Vec::new(),
+                listeners: Vec::new(),
+            }),
+        });
+        *node.this.borrow_mut() = Rc::downgrade(&node);
+        node
+    }
+
+    fn new_element(local_name: &str) -> Rc<MemoryNode> {
+        MemoryNode::new(Kind::Element(ElementData {
+            namespace: Some(HTML_NAMESPACE.to_owned()),
+            prefix: None,
+            local_name: local_name.to_ascii_lowercase(),
+            attributes: Vec::new(),
+        }))
+    }
+
+    fn html_document() -> Rc<MemoryNode> {
+        let document = MemoryNode::new(Kind::Document);
+        let html = MemoryNode::new_element("html");
+        html.insert_rc(MemoryNode::new_element("head"), None);
+        html.insert_rc(MemoryNode::new_element("body"), None);
+        document.insert_rc(html, None);
+        document
+    }
+
+    /// Build nodes from parsed html.
+    fn from_parsed(parsed: Parsed) -> Rc<MemoryNode> {
+        match parsed {
+            Parsed::Element {
+                name,
+                attributes,
+                children,
+            } => {
+                let element = MemoryNode::new_element(&name);
+                if let Kind::Element(data) = &mut element.data.borrow_mut().kind {
+                    data.attributes = attributes
+                        .into_iter()
+                        .map(|(local_name, value)| Attribute {
+                            namespace: None,
+                            prefix: None,
+                            local_name,
+                            value,
+                        })
+                        .collect();
+                }
+                for child in children {
+                    element.insert_rc(MemoryNode::from_parsed(child), None);
+                }
+                element
+            }
+            Parsed::Text(text) => MemoryNode::new(Kind::Text(text)),
+            Parsed::Comment(text) => MemoryNode::new(Kind::Comment(text)),
+        }
+    }
+
+    fn of(handle: &dyn Handle) -> Result<&MemoryNode, DomException> {
+        handle
+            .as_any()
+            .downcast_ref::<MemoryNode>()
+            .ok_or_else(DomException::mixed_backends)
+    }
+
+    fn rc(&self) -> Rc<MemoryNode> {
+        expect_opt!(
+            self.this.borrow().upgrade(),
+            "upgrading a live `MemoryNode`"
+        )
+    }
+
+    fn parent(&self) -> Option<Rc<MemoryNode>> {
+        self.data.borrow().parent.upgrade()
+    }
+
+    fn children(&self) -> Vec<Rc<MemoryNode>> {
+        self.data.borrow().children.clone()
+    }
+
+    fn with_element<T>(&self, f: impl FnOnce(&ElementData) -> T) -> Option<T> {
+        match &self.data.borrow().kind {
+            Kind::Element(data) => Some(f(data)),
+            _ => None,
+        }
+    }
+
+    fn with_element_mut<T>(&self, f: impl FnOnce(&mut ElementData) -> T) -> Option<T> {
+        match &mut self.data.borrow_mut().kind {
+            Kind::Element(data) => Some(f(data)),
+            _ => None,
+        }
+    }
+
+    fn is_element(&self) -> bool {
+        self.with_element(|_| ()).is_some()
+    }
+
+    fn is_document(&self) -> bool {
+        match self.data.borrow().kind {
+            Kind::Document => true,
+            _ => false,
+        }
+    }
+
+    fn text_data(&self) -> Option<String> {
+        match &self.data.borrow().kind {
+            Kind::Text(data) => Some(data.clone()),
+            _ => None,
+        }
+    }
+
+    fn attribute(&self, name: &str) -> Option<String> {
+        self.with_element(|data| data.attribute(name).map(str::to_owned))
+            .and_then(|attr| attr)
+    }
+
+    fn parent_element(&self) -> Option<Rc<MemoryNode>> {
+        self.parent().filter(|parent| parent.is_element())
+    }
+
+    /// The sibling `offset` places along from this node.
+    fn sibling(&self, offset: isize) -> Option<Rc<MemoryNode>> {
+        let parent = self.parent()?;
+        let data = parent.data.borrow();
+        let idx = data.children.iter().position(|child| same(child, self))?;
+        let idx = idx as isize + offset;
+        if idx < 0 {
+            return None;
+        }
+        data.children.get(idx as usize).cloned()
+    }
+
+    fn previous_element_sibling(&self) -> Option<Rc<MemoryNode>> {
+        let mut sibling = self.sibling(-1);
+        while let Some(node) = sibling {
+            if node.is_element() {
+                return Some(node);
+            }
+            sibling = node.sibling(-1);
+        }
+        None
+    }
+
+    fn next_element_sibling(&self) -> Option<Rc<MemoryNode>> {
+        let mut sibling = self.sibling(1);
+        while let Some(node) = sibling {
+            if node.is_element() {
+                return Some(node);
+            }
+            sibling = node.sibling(1);
+        }
+        None
+    }
+
+    /// This node followed by its ancestors, ending with the root.
+    fn inclusive_ancestors(&self) -> Vec<Rc<MemoryNode>> {
+        let mut out = vec![self.rc()];
+        while let Some(parent) = out[out.len() - 1].parent() {
+            out.push(parent);
+        }
+        out
+    }
+
+    /// All descendants in tree order, not including this node.
+    fn descendants(&self) -> Vec<Rc<MemoryNode>> {
+        let mut out = Vec::new();
+        for child in self.children() {
+            out.push(child.clone());
+            out.extend(child.descendants());
+        }
+        out
+    }
+
+    fn root(&self) -> Rc<MemoryNode> {
+        let mut ancestors = self.inclusive_ancestors();
+        expect_opt!(ancestors.pop(), "finding the root of a node")
+    }
+
+    fn is_inclusive_ancestor_of(&self, other: &MemoryNode) -> bool {
+        other
+            .inclusive_ancestors()
+            .iter()
+            .any(|ancestor| same(ancestor, self))
+    }
+
+    /// The position of this node in the tree, as child indices starting from the root.
+    fn tree_path(&self) -> Vec<usize> {
+        let mut path = Vec::new();
+        let mut node = self.rc();
+        while let Some(parent) = node.parent() {
+            let idx = parent
+                .data
+                .borrow()
+                .children
+                .iter()
+                .position(|child| Rc::ptr_eq(child, &node))
+                .unwrap_or(0);
+            path.push(idx);
+            node = parent;
+        }
+        path.reverse();
+        path
+    }
+
+    fn can_have_children(&self) -> bool {
+        match self.data.borrow().kind {
+            Kind::Document | Kind::Element(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Remove this node from its parent, if it has one.
+    fn detach(&self) {
+        if let Some(parent) = self.parent() {
+            parent
+                .data
+                .borrow_mut()
+                .children
+                .retain(|child| !same(child, self));
+        }
+        self.data.borrow_mut().parent = Weak::new();
+    }
+
+    /// Move `node` to before `reference` (or to the end) without checking validity.
+    fn insert_rc(&self, node: Rc<MemoryNode>, reference: Option<&MemoryNode>) {
+        node.detach();
+        let mut data = self.data.borrow_mut();
+        let idx = reference
+            .and_then(|reference| data.children.iter().position(|c| same(c, reference)))
+            .unwrap_or_else(|| data.children.len());
+        data.children.insert(idx, node.clone());
+        drop(data);
+        node.data.borrow_mut().parent = self.this.borrow().clone();
+    }
+
+    /// The checks from the spec's "ensure pre-insertion validity", plus the ones from "replace"
+    /// when `replacing` is set.
+    fn check_insert(
+        &self,
+        node: &MemoryNode,
+        child: Option<&MemoryNode>,
+        replacing: bool,
+    ) -> Result<(), DomException> {
+        if !self.can_have_children() {
+            return Err(hierarchy_request_error("this node cannot have children"));
+        }
+        if node.is_inclusive_ancestor_of(self) {
+            return Err(hierarchy_request_error(
+                "the new child is an ancestor of the parent",
+            ));
+        }
+        if let Some(child) = child {
+            if !child.parent().map_or(false, |parent| same(&parent, self)) {
+                return Err(not_a_child());
+            }
+        }
+        match node.data.borrow().kind {
+            Kind::Element(_) | Kind::Text(_) | Kind::Comment(_) => (),
+            _ => return Err(hierarchy_request_error("this node cannot be inserted")),
+        }
+        if self.is_document() {
+            if node.text_data().is_some() {
+                return Err(hierarchy_request_error(
+                    "text cannot be inserted into a document",
+                ));
+            }
+            let has_other_element = self.children().iter().any(|existing| {
+                existing.is_element()
+                    && !(replacing && child.map_or(false, |child| same(existing, child)))
+            });
+            if node.is_element() && has_other_element {
+                return Err(hierarchy_request_error(
+                    "a document can only have one element child",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn pre_insert(
+        &self,
+        node: &MemoryNode,
+        child: Option<&MemoryNode>,
+    ) -> Result<(), DomException> {
+        self.check_insert(node, child, false)?;
+        let reference = match child {
+            Some(child) if same(child, node) => node.sibling(1),
+            Some(child) => Some(child.rc()),
+            None => None,
+        };
+        self.insert_rc(node.rc(), reference.as_deref());
+        Ok(())
+    }
+
+    /// Insert the nodes built from some html at `position`.
+    fn insert_html(&self, position: InsertPosition, html: &str) -> Result<(), DomException> {
+        let (parent, reference) = match position {
+            InsertPosition::BeforeBegin | InsertPosition::AfterEnd => {
+                let parent = match self.parent() {
+                    Some(ref parent) if !parent.is_document() => parent.clone(),
+                    _ => {
+                        return Err(DomException::new(
+                            DomExceptionKind::NoModificationAllowed,
+                            "the element has no parent element",
+                        ))
+                    }
+                };
+                let reference = match position {
+                    InsertPosition::BeforeBegin => Some(self.rc()),
+                    _ => self.sibling(1),
+                };
+                (parent, reference)
+            }
+            InsertPosition::AfterBegin => (self.rc(), self.data.borrow().children.first().cloned()),
+            InsertPosition::BeforeEnd => (self.rc(), None),
+        };
+        for parsed in html::parse_fragment(html) {
+            parent.insert_rc(MemoryNode::from_parsed(parsed), reference.as_deref());
+        }
+        Ok(())
+    }
+
+    fn insert_adjacent(
+        &self,
+        position: InsertPosition,
+        node: &MemoryNode,
+    ) -> Result<Option<Rc<MemoryNode>>, DomException> {
+        match position {
+            InsertPosition::BeforeBegin => match self.parent() {
+                Some(parent) => parent.pre_insert(node, Some(self))?,
+                None => return Ok(None),
+            },
+            InsertPosition::AfterBegin => {
+                let first = self.data.borrow().children.first().cloned();
+                self.pre_insert(node, first.as_deref())?
+            }
+            InsertPosition::BeforeEnd => self.pre_insert(node, None)?,
+            InsertPosition::AfterEnd => match self.parent() {
+                Some(parent) => {
+                    let next = self.sibling(1);
+                    parent.pre_insert(node, next.as_deref())?
+                }
+                None => return Ok(None),
+            },
+        }
+        Ok(Some(node.rc()))
+    }
+
+    fn text_content_into(&self, out: &mut String) {
+        for child in self.children() {
+            match &child.data.borrow().kind {
+                Kind::Text(data) => out.push_str(data),
+                Kind::Element(_) => child.text_content_into(out),
+                _ => (),
+            }
+        }
+    }
+
+    fn deep_clone(&self, deep: bool) -> Rc<MemoryNode> {
+        let copy = MemoryNode::new(self.data.borrow().kind.clone());
+        if deep {
+            for child in self.children() {
+                copy.insert_rc(child.deep_clone(true), None);
+            }
+        }
+        copy
+    }
+
+    fn equals(&self, other: &MemoryNode) -> bool {
+        let same_kind = match (&self.data.borrow().kind, &other.data.borrow().kind) {
+            (Kind::Target, Kind::Target) | (Kind::Document, Kind::Document) => true,
+            (Kind::Element(a), Kind::Element(b)) => {
+                a.namespace == b.namespace
+                    && a.prefix == b.prefix
+                    && a.local_name == b.local_name
+                    && a.same_attributes(b)
+            }
+            (Kind::Text(a), Kind::Text(b)) | (Kind::Comment(a), Kind::Comment(b)) => a == b,
+            _ => false,
+        };
+        let (children, other_children) = (self.children(), other.children());
+        same_kind
+            && children.len() == other_children.len()
+            && children
+                .iter()
+                .zip(other_children.iter())
+                .all(|(a, b)| a.equals(b))
+    }
+
+    fn locate_namespace(&self, prefix: Option<&str>) -> Option<String> {
+        match prefix {
+            Some("xml") => return Some(XML_NAMESPACE.to_owned()),
+            Some("xmlns") => return Some(XMLNS_NAMESPACE.to_owned()),
+            _ => (),
+        }
+        let element = match self.data.borrow().kind {
+            Kind::Element(_) => self.rc(),
+            Kind::Document => {
+                return self
+                    .children()
+                    .into_iter()
+                    .find(|c| c.is_element())?
+                    .locate_namespace(prefix)
+            }
+            Kind::Target => return None,
+            _ => return self.parent_element()?.locate_namespace(prefix),
+        };
+        let found = element.with_element(|data| {
+            if data.namespace.is_some() && data.prefix.as_deref() == prefix {
+                return data.namespace.clone();
+            }
+            let declaration = match prefix {
+                Some(prefix) => format!("xmlns:{}", prefix),
+                None => "xmlns".to_owned(),
+            };
+            data.attribute(&declaration)
+                .filter(|value| !value.is_empty())
+                .map(str::to_owned)
+        });
+        match found {
+            Some(Some(namespace)) => Some(namespace),
+            _ => element.parent_element()?.locate_namespace(prefix),
+        }
+    }
+
+    fn locate_prefix(&self, namespace: &str) -> Option<String> {
+        let element = match self.data.borrow().kind {
+            Kind::Element(_) => self.rc(),
+            Kind::Document => {
+                return self
+                    .children()
+                    .into_iter()
+                    .find(|c| c.is_element())?
+                    .locate_prefix(namespace)
+            }
+            Kind::Target => return None,
+            _ => return self.parent_element()?.locate_prefix(namespace),
+        };
+        let found = element.with_element(|data| {
+            if data.namespace.as_deref() == Some(namespace) && data.prefix.is_some() {
+                return data.prefix.clone();
+            }
+            data.attributes
+                .iter()
+                .find(|attr| attr.prefix.as_deref() == Some("xmlns") && attr.value == namespace)
+                .map(|attr| attr.local_name.clone())
+                .or_else(|| {
+                    data.attributes.iter().find_map(|attr| {
+                        let name = attr.qualified_name();
+                        if attr.value == namespace && name.starts_with("xmlns:") {
+                            Some(name["xmlns:".len()..].to_owned())
+                        } else {
+                            None
+                        }
+                    })
+                })
+        });
+        match found {
+            Some(Some(prefix)) => Some(prefix),
+            _ => element.parent_element()?.locate_prefix(namespace),
+        }
+    }
+
+    fn serialize_into(&self, out: &mut String) {
+        match &self.data.borrow().kind {
+            Kind::Element(data) => {
+                let name = data.qualified_name();
+                out.push('<');
+                out.push_str(&name);
+                for attr in &data.attributes {
+                    out.push(' ');
+                    out.push_str(&attr.qualified_name());
+                    out.push_str("=\"");
+                    html::escape_attribute_into(&attr.value, out);
+                    out.push('"');
+                }
+                out.push('>');
+                if data.is_html() && html::is_void(&data.local_name) {
+                    return;
+                }
+                self.serialize_children_into(out);
+                out.push_str("</");
+                out.push_str(&name);
+                out.push('>');
+            }
+            Kind::Text(text) => {
+                let raw = self
+                    .parent_element()
+                    .and_then(|parent| {
+                        parent.with_element(|data| html::is_raw_text(&data.local_name))
+                    })
+                    .unwrap_or(false);
+                if raw {
+                    out.push_str(text);
+                } else {
+                    html::escape_text_into(text, out);
+                }
+            }
+            Kind::Comment(text) => {
+                out.push_str("<!--");
+                out.push_str(text);
+                out.push_str("-->");
+            }
+            Kind::Document => self.serialize_children_into(out),
+            Kind::Target => (),
+        }
+    }
+
+    fn serialize_children_into(&self, out: &mut String) {
+        for child in self.children() {
+            child.serialize_into(out);
+        }
+    }
+
+    fn remove_listener(&self, registered: &Rc<Registered>) {
+        registered.removed.set(true);
+        self.data
+            .borrow_mut()
+            .listeners
+            .retain(|other| !Rc::ptr_eq(other, registered));
+    }
+
+    /// Call the listeners on this node for one step of the event path.
+    fn invoke(&self, event: &Event, state: &MemoryEvent, phase: EventPhase) {
+        let mut listeners: Vec<Rc<Registered>> = self
+            .data
+            .borrow()
+            .listeners
+            .iter()
+            .filter(|listener| {
+                listener.kind == state.kind
+                    && match phase {
+                        EventPhase::Capturing => listener.capture,
+                        EventPhase::AtTarget => true,
+                        EventPhase::Bubbling => !listener.capture,
+                        EventPhase::None => false,
+                    }
+            })
+            .cloned()
+            .collect();
+        // At the target, capturing listeners still run first.
+        listeners.sort_by_key(|listener| !listener.capture);
+        state.phase.set(phase);
+        *state.current_target.borrow_mut() = Some(self.rc());
+        for listener in listeners {
+            if state.stop_immediate_propagation.get() {
+                break;
+            }
+            // A listener may have been removed by an earlier one.
+            if listener.removed.get() {
+                continue;
+            }
+            if listener.once {
+                self.remove_listener(&listener);
+            }
+            state.in_passive_listener.set(listener.passive);
+            (listener.callback)(event.clone());
+            state.in_passive_listener.set(false);
+        }
+    }
+}
+
+impl Handle for MemoryNode {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn add_event_listener(
+        &self,
+        kind: &str,
+        listener: Listener,
+        options: &AddEventListenerOptions,
+    ) -> Result<Unsubscribe, DomException> {
+        let registered = Rc::new(Registered {
+            kind: kind.to_owned(),
+            capture: options.capture.unwrap_or(false),
+            once: options.once.unwrap_or(false),
+            passive: options.passive.unwrap_or(false),
+            callback: listener,
+            removed: Cell::new(false),
+        });
+        self.data.borrow_mut().listeners.push(registered.clone());
+        let node = self.this.borrow().clone();
+        Ok(Box::new(move || {
+            if let Some(node) = node.upgrade() {
+                node.remove_listener(&registered);
+            }
+        }))
+    }
+
+    fn is_same_target(&self, other: &dyn Handle) -> bool {
+        MemoryNode::of(other).map_or(false, |other| same(self, other))
+    }
+
+    fn dispatch_event(&self, event: &Event) -> Result<bool, DomException> {
+        let event = if event.raw.as_any().is::<MemoryEvent>() {
+            event.clone()
+        } else {
+            // The event comes from another backend, so make our own copy of it.
+            Event::from_handle(Rc::new(MemoryEvent::new(
+                event.raw.kind(),
+                event.raw.bubbles(),
+                event.raw.cancelable(),
+                event.raw.composed(),
+                event.raw.data(),
+            )))
+        };
+        let state = expect_opt!(
+            event.raw.as_any().downcast_ref::<MemoryEvent>(),
+            "downcasting to a `MemoryEvent`"
+        );
+        if state.dispatching.get() {
+            return Err(DomException::new(
+                DomExceptionKind::InvalidState,
+                "the event is already being dispatched",
+            ));
+        }
+        state.dispatching.set(true);
+        let path = self.inclusive_ancestors();
+        *state.target.borrow_mut() = Some(self.rc());
+        *state.path.borrow_mut() = path
+            .iter()
+            .map(|node| node.clone() as Rc<dyn Handle>)
+            .collect();
+        let capturing = path[1..]
+            .iter()
+            .rev()
+            .map(|node| (node, EventPhase::Capturing));
+        let at_target = path[..1].iter().map(|node| (node, EventPhase::AtTarget));
+        let bubbling = path[1..]
+            .iter()
+            .filter(|_| state.bubbles)
+            .map(|node| (node, EventPhase::Bubbling));
+        for (node, phase) in capturing.chain(at_target).chain(bubbling) {
+            if state.stop_propagation.get() {
+                break;
+            }
+            node.invoke(&event, state, phase);
+        }
+        state.phase.set(EventPhase::None);
+        *state.current_target.borrow_mut() = None;
+        state.path.borrow_mut().clear();
+        state.stop_propagation.set(false);
+        state.stop_immediate_propagation.set(false);
+        state.dispatching.set(false);
+        Ok(!state.canceled.get())
+    }
+
+    fn is_node(&self) -> bool {
+        !matches!(self.data.borrow().kind, Kind::Target)
+    }
+    fn node_type(&self) -> NodeType {
+        match self.data.borrow().kind {
+            Kind::Document => NodeType::Document,
+            Kind::Element(_) => NodeType::Element,
+            Kind::Text(_) => NodeType::Text,
+            Kind::Comment(_) => NodeType::Comment,
+            Kind::Target => unreachable!("plain event targets are not nodes"),
+        }
+    }
+    fn node_name(&self) -> String {
+        match &self.data.borrow().kind {
+            Kind::Document => "#document".to_owned(),
+            Kind::Element(_) => self.tag_name(),
+            Kind::Text(_) => "#text".to_owned(),
+            Kind::Comment(_) => "#comment".to_owned(),
+            Kind::Target => String::new(),
+        }
+    }
+    fn append_child(&self, child: &dyn Handle) -> Result<(), DomException> {
+        self.pre_insert(MemoryNode::of(child)?, None)
+    }
+    fn clone_node(&self, deep: bool) -> Result<Rc<dyn Handle>, DomException> {
+        Ok(self.deep_clone(deep))
+    }
+    fn compare_document_position(&self, other: &dyn Handle) -> DocumentPosition {
+        let other = match MemoryNode::of(other) {
+            Ok(other) => other,
+            Err(_) => {
+                return DocumentPosition::DISCONNECTED | DocumentPosition::IMPLEMENTATION_SPECIFIC
+            }
+        };
+        if same(self, other) {
+            return DocumentPosition::empty();
+        }
+        if !same(&self.root(), &other.root()) {
+            // Any ordering is fine as long as it is consistent.
+            let order = if (other as *const MemoryNode) < (self as *const MemoryNode) {
+                DocumentPosition::PRECEDING
+            } else {
+                DocumentPosition::FOLLOWING
+            };
+            return DocumentPosition::DISCONNECTED
+                | DocumentPosition::IMPLEMENTATION_SPECIFIC
+                | order;
+        }
+        if other.is_inclusive_ancestor_of(self) {
+            DocumentPosition::CONTAINS | DocumentPosition::PRECEDING
+        } else if self.is_inclusive_ancestor_of(other) {
+            DocumentPosition::CONTAINED_BY | DocumentPosition::FOLLOWING
+        } else if other.tree_path() < self.tree_path() {
+            DocumentPosition::PRECEDING
+        } else {
+            DocumentPosition::FOLLOWING
+        }
+    }
+    fn contains(&self, other: &dyn Handle) -> bool {
+        match MemoryNode::of(other) {
+            Ok(other) => self.is_inclusive_ancestor_of(other),
+            Err(_) => false,
+        }
+    }
+    fn get_root_node(&self) -> Rc<dyn Handle> {
+        self.root()
+    }
+    fn has_child_nodes(&self) -> bool {
+        !self.data.borrow().children.is_empty()
+    }
+    fn insert_before(
+        &self,
+        node: &dyn Handle,
+        child: Option<&dyn Handle>,
+    ) -> Result<(), DomException> {
+        let child = match child {
+            Some(child) => Some(MemoryNode::of(child)?),
+            None => None,
+        };
+        self.pre_insert(MemoryNode::of(node)?, child)
+    }
+    fn is_default_namespace(&self, namespace: Option<&str>) -> bool {
+        let namespace = namespace.filter(|namespace| !namespace.is_empty());
+        self.locate_namespace(None).as_deref() == namespace
+    }
+    fn is_equal_node(&self, other: &dyn Handle) -> bool {
+        match MemoryNode::of(other) {
+            Ok(other) => self.equals(other),
+            Err(_) => false,
+        }
+    }
+    fn is_same_node(&self, other: &dyn Handle) -> bool {
+        match MemoryNode::of(other) {
+            Ok(other) => same(self, other),
+            Err(_) => false,
+        }
+    }
+    fn lookup_namespace_uri(&self, prefix: Option<&str>) -> Option<String> {
+        self.locate_namespace(prefix.filter(|prefix| !prefix.is_empty()))
+    }
+    fn lookup_prefix(&self, namespace: Option<&str>) -> Option<String> {
+        self.locate_prefix(namespace.filter(|namespace| !namespace.is_empty())?)
+    }
+    fn normalize(&self) {
+        let mut previous_text: Option<Rc<MemoryNode>> = None;
+        for child in self.children() {
+            match child.text_data() {
+                Some(data) if data.is_empty() => child.detach(),
+                Some(data) => match &previous_text {
+                    Some(previous) => {
+                        if let Kind::Text(text) = &mut previous.data.borrow_mut().kind {
+                            text.push_str(&data);
+                        }
+                        child.detach();
+                    }
+                    None => previous_text = Some(child),
+                },
+                None => {
+                    previous_text = None;
+                    child.normalize();
+                }
+            }
+        }
+    }
+    fn remove_child(&self, child: &dyn Handle) -> Result<Rc<dyn Handle>, DomException> {
+        let child = MemoryNode::of(child)?;
+        if !child.parent().map_or(false, |parent| same(&parent, self)) {
+            return Err(not_a_child());
+        }
+        child.detach();
+        Ok(child.rc())
+    }
+    fn replace_child(
+        &self,
+        node: &dyn Handle,
+        child: &dyn Handle,
+    ) -> Result<Rc<dyn Handle>, DomException> {
+        let (node, child) = (MemoryNode::of(node)?, MemoryNode::of(child)?);
+        self.check_insert(node, Some(child), true)?;
+        if same(node, child) {
+            return Ok(child.rc());
+        }
+        let reference = match child.sibling(1) {
+            Some(next) if same(&next, node) => node.sibling(1),
+            next => next,
+        };
+        child.detach();
+        self.insert_rc(node.rc(), reference.as_deref());
+        Ok(child.rc())
+    }
+    fn base_uri(&self) -> Result<Option<String>, DomException> {
+        Ok(Some("about:blank".to_owned()))
+    }
+    fn is_connected(&self) -> bool {
+        self.root().is_document()
+    }
+    fn parent_node(&self) -> Option<Rc<dyn Handle>> {
+        self.parent().map(|parent| parent as Rc<dyn Handle>)
+    }
+    fn first_child(&self) -> Option<Rc<dyn Handle>> {
+        let child = self.data.borrow().children.first().cloned();
+        child.map(|child| child as Rc<dyn Handle>)
+    }
+    fn last_child(&self) -> Option<Rc<dyn Handle>> {
+        let child = self.data.borrow().children.last().cloned();
+        child.map(|child| child as Rc<dyn Handle>)
+    }
+    fn previous_sibling(&self) -> Option<Rc<dyn Handle>> {
+        self.sibling(-1).map(|sibling| sibling as Rc<dyn Handle>)
+    }
+    fn next_sibling(&self) -> Option<Rc<dyn Handle>> {
+        self.sibling(1).map(|sibling| sibling as Rc<dyn Handle>)
+    }
+    fn node_value(&self) -> Option<String> {
+        match &self.data.borrow().kind {
+            Kind::Text(data) | Kind::Comment(data) => Some(data.clone()),
+            _ => None,
+        }
+    }
+    fn set_node_value(&self, node_value: Option<&str>) {
+        match &mut self.data.borrow_mut().kind {
+            Kind::Text(data) | Kind::Comment(data) => *data = node_value.unwrap_or("").to_owned(),
+            _ => (),
+        }
+    }
+    fn text_content(&self) -> Option<String> {
+        match &self.data.borrow().kind {
+            Kind::Text(data) | Kind::Comment(data) => return Some(data.clone()),
+            Kind::Element(_) => (),
+            Kind::Document | Kind::Target => return None,
+        }
+        let mut out = String::new();
+        self.text_content_into(&mut out);
+        Some(out)
+    }
+    fn set_text_content(&self, text_content: Option<&str>) {
+        let text_content = text_content.unwrap_or("");
+        match &mut self.data.borrow_mut().kind {
+            Kind::Text(data) | Kind::Comment(data) => {
+                *data = text_content.to_owned();
+                return;
+            }
+            Kind::Element(_) => (),
+            Kind::Document | Kind::Target => return,
+        }
+        for child in self.children() {
+            child.detach();
+        }
+        if !text_content.is_empty() {
+            self.insert_rc(MemoryNode::new(Kind::Text(text_content.to_owned())), None);
+        }
+    }
+
+    fn closest(&self, selector: &str) -> Result<Option<Rc<dyn Handle>>, DomException> {
+        let selector = SelectorList::parse(selector)?;
+        Ok(self
+            .inclusive_ancestors()
+            .into_iter()
+            .find(|node| node.is_element() && selector.matches(node))
+            .map(|node| node as Rc<dyn Handle>))
+    }
+    fn matches(&self, selector: &str) -> Result<bool, DomException> {
+        Ok(SelectorList::parse(selector)?.matches(self))
+    }
+    fn query_selector(&self, selectors: &str) -> Result<Option<Rc<dyn Handle>>, DomException> {
+        Ok(self.query_selector_all(selectors)?.into_iter().next())
+    }
+    fn query_selector_all(&self, selectors: &str) -> Result<Vec<Rc<dyn Handle>>, DomException> {
+        let selector = SelectorList::parse(selectors)?;
+        Ok(self
+            .descendants()
+            .into_iter()
+            .filter(|node| node.is_element() && selector.matches(node))
+            .map(|node| node as Rc<dyn Handle>)
+            .collect())
+    }
+    fn get_attribute(&self, name: &str) -> Option<String> {
+        self.attribute(name)
+    }
+    fn get_attribute_ns(&self, namespace: Option<&str>, local_name: &str) -> Option<String> {
+        self.with_element(|data| {
+            data.find_ns(namespace, local_name)
+                .map(|idx| data.attributes[idx].value.clone())
+        })
+        .and_then(|attr| attr)
+    }
+    fn has_attribute(&self, name: &str) -> bool {
+        self.attribute(name).is_some()
+    }
+    fn has_attribute_ns(&self, namespace: Option<&str>, local_name: &str) -> bool {
+        self.get_attribute_ns(namespace, local_name).is_some()
+    }
+    fn has_attributes(&self) -> bool {
+        self.with_element(|data| !data.attributes.is_empty())
+            .unwrap_or(false)
+    }
+    fn set_attribute(&self, name: &str, value: &str) -> Result<(), DomException> {
+        validate_name(name)?;
+        self.with_element_mut(|data| match data.find(name) {
+            Some(idx) => data.attributes[idx].value = value.to_owned(),
+            None => {
+                let local_name = data.normalize_name(name);
+                data.attributes.push(Attribute {
+                    namespace: None,
+                    prefix: None,
+                    local_name,
+                    value: value.to_owned(),
+                })
+            }
+        });
+        Ok(())
+    }
+    fn set_attribute_ns(
+        &self,
+        namespace: Option<&str>,
+        name: &str,
+        value: &str,
+    ) -> Result<(), DomException> {
+        validate_name(name)?;
+        let namespace = namespace.filter(|namespace| !namespace.is_empty());
+        let mut parts = name.splitn(2, ':');
+        let (prefix, local_name) = match (parts.next(), parts.next()) {
+            (Some(prefix), Some(local_name)) => (Some(prefix), local_name),
+            (Some(local_name), None) => (None, local_name),
+            _ => unreachable!("splitn always returns at least one part"),
+        };
+        if prefix.is_some() && namespace.is_none() {
+            return Err(DomException::new(
+                DomExceptionKind::Namespace,
+                "a prefixed attribute must have a namespace",
+            ));
+        }
+        self.with_element_mut(|data| match data.find_ns(namespace, local_name) {
+            Some(idx) => data.attributes[idx].value = value.to_owned(),
+            None => data.attributes.push(Attribute {
+                namespace: namespace.map(str::to_owned),
+                prefix: prefix.map(str::to_owned),
+                local_name: local_name.to_owned(),
+                value: value.to_owned(),
+            }),
+        });
+        Ok(())
+    }
+    fn remove_attribute(&self, name: &str) -> Result<(), DomException> {
+        self.with_element_mut(|data| {
+            if let Some(idx) = data.find(name) {
+                data.attributes.remove(idx);
+            }
+        });
+        Ok(())
+    }
+    fn remove_attribute_ns(
+        &self,
+        namespace: Option<&str>,
+        local_name: &str,
+    ) -> Result<(), DomException> {
+        let namespace = namespace.filter(|namespace| !namespace.is_empty());
+        self.with_element_mut(|data| {
+            if let Some(idx) = data.find_ns(namespace, local_name) {
+                data.attributes.remove(idx);
+            }
+        });
+        Ok(())
+    }
+    fn toggle_attribute(&self, name: &str) -> Result<bool, DomException> {
+        validate_name(name)?;
+        if self.has_attribute(name) {
+            self.remove_attribute(name)?;
+            Ok(false)
+        } else {
+            self.set_attribute(name, "")?;
+            Ok(true)
+        }
+    }
+    fn insert_adjacent_element(
+        &self,
+        position: InsertPosition,
+        element: &dyn Handle,
+    ) -> Result<Option<Rc<dyn Handle>>, DomException> {
+        Ok(self
+            .insert_adjacent(position, MemoryNode::of(element)?)?
+            .map(|node| node as Rc<dyn Handle>))
+    }
+    fn insert_adjacent_html(
+        &self,
+        position: InsertPosition,
+        data: &str,
+    ) -> Result<(), DomException> {
+        self.insert_html(position, data)
+    }
+    fn insert_adjacent_text(
+        &self,
+        position: InsertPosition,
+        text: &str,
+    ) -> Result<(), DomException> {
+        let text = MemoryNode::new(Kind::Text(text.to_owned()));
+        self.insert_adjacent(position, &text).map(|_| ())
+    }
+    fn namespace_uri(&self) -> Option<String> {
+        self.with_element(|data| data.namespace.clone())
+            .and_then(|namespace| namespace)
+    }
+    fn prefix(&self) -> Option<String> {
+        self.with_element(|data| data.prefix.clone())
+            .and_then(|prefix| prefix)
+    }
+    fn local_name(&self) -> String {
+        self.with_element(|data| data.local_name.clone())
+            .unwrap_or_default()
+    }
+    fn tag_name(&self) -> String {
+        self.with_element(|data| {
+            let name = data.qualified_name();
+            if data.is_html() {
+                name.to_ascii_uppercase()
+            } else {
+                name
+            }
+        })
+        .unwrap_or_default()
+    }
+    fn inner_html(&self) -> String {
+        let mut out = String::new();
+        self.serialize_children_into(&mut out);
+        out
+    }
+    fn set_inner_html(&self, inner_html: &str) {
+        for child in self.children() {
+            child.detach();
+        }
+        for parsed in html::parse_fragment(inner_html) {
+            self.insert_rc(MemoryNode::from_parsed(parsed), None);
+        }
+    }
+    fn outer_html(&self) -> String {
+        let mut out = String::new();
+        self.serialize_into(&mut out);
+        out
+    }
+    fn set_outer_html(&self, outer_html: &str) {
+        // The browser throws when the parent is a document, and does nothing when there is no
+        // parent.
+        let _ = self.insert_html(InsertPosition::BeforeBegin, outer_html);
+        if self.parent_element().is_some() {
+            self.detach();
+        }
+    }
+    fn first_element_child(&self) -> Option<Rc<dyn Handle>> {
+        self.children()
+            .into_iter()
+            .find(|child| child.is_element())
+            .map(|child| child as Rc<dyn Handle>)
+    }
+    fn last_element_child(&self) -> Option<Rc<dyn Handle>> {
+        self.children()
+            .into_iter()
+            .rev()
+            .find(|child| child.is_element())
+            .map(|child| child as Rc<dyn Handle>)
+    }
+    fn child_element_count(&self) -> u32 {
+        self.children()
+            .iter()
+            .filter(|child| child.is_element())
+            .count() as u32
+    }
+
+    fn body(&self) -> Option<Rc<dyn Handle>> {
+        let html = self
+            .children()
+            .into_iter()
+            .find(|child| child.is_element())?;
+        html.children()
+            .into_iter()
+            .find(|child| {
+                child
+                    .with_element(|data| data.is_html() && data.local_name == "body")
+                    .unwrap_or(false)
+            })
+            .map(|body| body as Rc<dyn Handle>)
+    }
+    fn create_element(&self, tag_name: &str) -> Result<Rc<dyn Handle>, DomException> {
+        validate_name(tag_name)?;
+        Ok(MemoryNode::new_element(tag_name))
+    }
+    fn create_text_node(&self, data: &str) -> Rc<dyn Handle> {
+        MemoryNode::new(Kind::Text(data.to_owned()))
+    }
+}
+
+/// An event in the in-memory DOM.
+#[derive(Debug)]
+pub struct MemoryEvent {
+    kind: String,
+    bubbles: bool,
+    cancelable: bool,
+    composed: bool,
+    data: EventData,
+    time_stamp: f64,
+    dispatching: Cell<bool>,
+    phase: Cell<EventPhase>,
+    canceled: Cell<bool>,
+    in_passive_listener: Cell<bool>,
+    stop_propagation: Cell<bool>,
+    stop_immediate_propagation: Cell<bool>,
+    target: RefCell<Option<Rc<dyn Handle>>>,
+    current_target: RefCell<Option<Rc<dyn Handle>>>,
+    path: RefCell<Vec<Rc<dyn Handle>>>,
+}
+
+impl MemoryEvent {
+    fn new(kind: String, bubbles: bool, cancelable: bool, composed: bool, data: EventData) -> Self {
+        MemoryEvent {
+            kind,
+            bubbles,
+            cancelable,
+            composed,
+            data,
+            time_stamp: now(),
+            dispatching: Cell::new(false),
+            phase: Cell::new(EventPhase::None),
+            canceled: Cell::new(false),
+            in_passive_listener: Cell::new(false),
+            stop_propagation: Cell::new(false),
+            stop_immediate_propagation: Cell::new(false),
+            target: RefCell::new(None),
+            current_target: RefCell::new(None),
+            path: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+/// Milliseconds since the first time this was called on this thread.
+#[cfg(not(target_arch = "wasm32"))]
+fn now() -> f64 {
+    use std::time::Instant;
+
+    thread_local! {
+        static START: Instant = Instant::now();
+    }
+    START.with(|start| start.elapsed().as_secs_f64() * 1000.)
+}
+
+/// Milliseconds since the epoch, since `Instant` isn't available on wasm.
+#[cfg(target_arch = "wasm32")]
+fn now() -> f64 {
+    js_sys::Date::now()
+}
+
+impl EventHandle for MemoryEvent {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn kind(&self) -> String {
+        self.kind.clone()
+    }
+    fn bubbles(&self) -> bool {
+        self.bubbles
+    }
+    fn cancelable(&self) -> bool {
+        self.cancelable
+    }
+    fn composed(&self) -> bool {
+        self.composed
+    }
+    fn data(&self) -> EventData {
+        self.data.clone()
+    }
+    fn is_trusted(&self) -> bool {
+        false
+    }
+    fn time_stamp(&self) -> f64 {
+        self.time_stamp
+    }
+    fn event_phase(&self) -> EventPhase {
+        self.phase.get()
+    }
+    fn prevent_default(&self) {
+        if self.cancelable && !self.in_passive_listener.get() {
+            self.canceled.set(true);
+        }
+    }
+    fn default_prevented(&self) -> bool {
+        self.canceled.get()
+    }
+    fn stop_propagation(&self) {
+        self.stop_propagation.set(true);
+    }
+    fn stop_immediate_propagation(&self) {
+        self.stop_propagation.set(true);
+        self.stop_immediate_propagation.set(true);
+    }
+    fn target(&self) -> Option<Rc<dyn Handle>> {
+        self.target.borrow().clone()
+    }
+    fn current_target(&self) -> Option<Rc<dyn Handle>> {
+        self.current_target.borrow().clone()
+    }
+    fn composed_path(&self) -> Vec<Rc<dyn Handle>> {
+        self.path.borrow().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::{EventKind, EventTarget},
+        node::Node,
+    };
+
+    fn document() -> crate::document::Document {
+        crate::document::Document::from_handle(MemoryBackend.new_document())
+    }
+
+    #[test]
+    fn tree_mutation() {
+        let document = document();
+        let list = document.create_element("ul");
+        let first = document.create_element("li");
+        let second = document.create_element("li");
+        list.append_child(&first);
+        list.insert_before(first.clone(), None::<Node>);
+        list.insert_before(second.clone(), Some(first.clone()));
+        assert!(list.first_child().unwrap().is_same_node(second.clone()));
+        assert!(list.last_child().unwrap().is_same_node(first.clone()));
+        assert_eq!(list.child_element_count(), 2);
+
+        list.remove_child(second.clone()).unwrap();
+        assert!(second.parent_node().is_none());
+        assert_eq!(list.child_element_count(), 1);
+        assert_eq!(
+            *list.remove_child(second.clone()).unwrap_err().kind(),
+            DomExceptionKind::NotFound
+        );
+
+        list.replace_child(second.clone(), first.clone()).unwrap();
+        assert!(list.first_child().unwrap().is_same_node(second.clone()));
+        assert!(!list.contains(first.clone()));
+
+        assert!(!list.is_connected());
+        document.body().append_child(&list);
+        assert!(second.is_connected());
+        assert!(document
+            .body()
+            .compare_document_position(second.clone())
+            .contains(DocumentPosition::CONTAINED_BY));
+    }
+
+    #[test]
+    fn hierarchy_errors() {
+        let document = document();
+        let outer = document.create_element("div");
+        let inner = document.create_element("div");
+        outer.append_child(&inner);
+        let err = inner.raw().append_child(outer.raw()).unwrap_err();
+        assert_eq!(*err.kind(), DomExceptionKind::HierarchyRequest);
+        let text = document.create_text_node("text");
+        assert_eq!(
+            text.raw().append_child(inner.raw()).unwrap_err().name(),
+            "HierarchyRequestError"
+        );
+    }
+
+    #[test]
+    fn attributes() {
+        let document = document();
+        let el = document.create_element("div");
+        assert!(!el.has_attributes());
+        assert!(el.set_attribute("Data-Value", "1").is_ok());
+        assert_eq!(el.get_attribute("data-value"), Some("1".to_owned()));
+        assert!(el.set_attribute("not valid", "1").is_err());
+        assert_eq!(el.toggle_attribute("hidden").ok(), Some(true));
+        assert!(el.has_attribute("hidden"));
+        assert_eq!(el.toggle_attribute("hidden").ok(), Some(false));
+        el.set_id("main");
+        el.set_class_name("a b");
+        assert_eq!(el.id(), "main");
+        el.remove_attribute("data-value");
+        assert_eq!(el.outer_html(), r#"<div id="main" class="a b"></div>"#);
+        assert_eq!(el.tag_name(), "DIV");
+        assert_eq!(el.local_name(), "div");
+    }
+
+    #[test]
+    fn text_content() {
+        let document = document();
+        let el = document.create_element("p");
+        el.set_inner_html("Hello, <b>world</b>!<!-- comment -->");
+        assert_eq!(el.text_content(), Some("Hello, world!".to_owned()));
+        assert_eq!(el.inner_html(), "Hello, <b>world</b>!<!-- comment -->");
+        el.set_text_content(Some("a < b"));
+        assert_eq!(el.inner_html(), "a &lt; b");
+        assert_eq!(el.child_element_count(), 0);
+
+        el.append_child(&document.create_text_node(""));
+        el.append_child(&document.create_text_node(" & c"));
+        el.normalize();
+        assert_eq!(
+            el.first_child().unwrap().node_value(),
+            Some("a < b & c".to_owned())
+        );
+        assert!(el.first_child().unwrap().next_sibling().is_none());
+    }
+
+    #[test]
+    fn selectors() {
+        let document = document();
+        let body = document.body();
+        body.set_inner_html(
+            r#"<ul id="list"><li class="row first">a</li><li class="row" data-x="1">b</li></ul>"#,
+        );
+        let second = body
+            .query_selector("ul > li.row + li[data-x='1']")
+            .ok()
+            .unwrap()
+            .unwrap();
+        assert_eq!(second.text_content(), Some("b".to_owned()));
+        assert!(second.matches("#list li:last-child").ok().unwrap());
+        assert!(!second.matches(".first").ok().unwrap());
+        let list = second.closest("ul").ok().unwrap().unwrap();
+        assert_eq!(list.id(), "list");
+        assert_eq!(body.query_selector_all("li, ul").ok().unwrap().len(), 3);
+        assert_eq!(
+            *body.query_selector("li[").unwrap_err().kind(),
+            DomExceptionKind::Syntax
+        );
+    }
+
+    #[test]
+    fn capture_and_bubble() {
+        let document = document();
+        let outer = document.create_element("div");
+        let inner = document.create_element("span");
+        outer.append_child(&inner);
+        document.body().append_child(&outer);
+
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let listen = |target: &EventTarget, name: &'static str, capture: bool| {
+            let log = log.clone();
+            let mut options = AddEventListenerOptions::default();
+            options.capture(capture);
+            target.add_event_listener_opts(
+                EventKind::Click,
+                move |_| log.borrow_mut().push(name),
+                options,
+            )
+        };
+        let _guards = [
+            listen(&outer, "outer bubble", false),
+            listen(&outer, "outer capture", true),
+            listen(&inner, "inner bubble", false),
+            listen(&inner, "inner capture", true),
+            listen(&document, "document capture", true),
+        ];
+
+        let mut init = EventInit::default();
+        init.bubbles(true);
+        inner.dispatch_event(Event::new_with_init(EventKind::Click, &init));
+        assert_eq!(
+            *log.borrow(),
+            vec![
+                "document capture",
+                "outer capture",
+                "inner capture",
+                "inner bubble",
+                "outer bubble"
+            ]
+        );
+
+        // Events that don't bubble still go through the capture phase.
+        log.borrow_mut().clear();
+        inner.dispatch_event(EventKind::Click);
+        assert_eq!(
+            *log.borrow(),
+            vec![
+                "document capture",
+                "outer capture",
+                "inner capture",
+                "inner bubble"
+            ]
+        );
+    }
+
+    #[test]
+    fn once_and_unsubscribe() {
+        let target = EventTarget::new();
+        let count = Rc::new(Cell::new(0));
+        let mut options = AddEventListenerOptions::default();
+        options.once(true);
+        let count_copy = count.clone();
+        let _once = target.add_event_listener_opts(
+            EventKind::Click,
+            move |_| count_copy.set(count_copy.get() + 1),
+            options,
+        );
+        let count_copy = count.clone();
+        let guard = target.add_event_listener(EventKind::Click, move |_| {
+            count_copy.set(count_copy.get() + 10)
+        });
+        target.dispatch_event(EventKind::Click);
+        target.dispatch_event(EventKind::Click);
+        assert_eq!(count.get(), 21);
+        drop(guard);
+        target.dispatch_event(EventKind::Click);
+        assert_eq!(count.get(), 21);
+    }
+}
@@ -0,0 +1,383 @@
+//! A small css selector engine.
+//!
+//! Supports type, universal, id, class and attribute selectors, the structural pseudo-classes
+//! `:first-child`, `:last-child`, `:only-child`, `:empty` and `:root`, `:not(..)`, and all four
+//! combinators. Anything else is a `SyntaxError`.
+
+use super::MemoryNode;
+use crate::error::{DomException, DomExceptionKind};
+
+#[derive(Debug)]
+pub(super) struct SelectorList(Vec<Complex>);
+
+/// Compound selectors joined by combinators, so `combinators[i]` sits between `compounds[i]`
+/// and `compounds[i + 1]`.
+#[derive(Debug)]
+struct Complex {
+    compounds: Vec<Vec<Simple>>,
+    combinators: Vec<Combinator>,
+}
+
+#[derive(Debug)]
+enum Simple {
+    Universal,
+    Type(String),
+    Id(String),
+    Class(String),
+    Attribute(String, Option<(AttributeOp, String)>),
+    FirstChild,
+    LastChild,
+    OnlyChild,
+    Empty,
+    Root,
+    Not(SelectorList),
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum AttributeOp {
+    Equals,
+    Includes,
+    DashMatch,
+    Prefix,
+    Suffix,
+    Substring,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Combinator {
+    Descendant,
+    Child,
+    NextSibling,
+    SubsequentSibling,
+}
+
+impl SelectorList {
+    pub(super) fn parse(input: &str) -> Result<SelectorList, DomException> {
+        let mut parser = Parser {
+            input,
+            chars: input.chars().collect(),
+            pos: 0,
+        };
+        let list = parser.selector_list()?;
+        parser.skip_whitespace();
+        if parser.peek().is_some() {
+            return Err(parser.error());
+        }
+        Ok(list)
+    }
+
+    /// Whether `element` matches any of the selectors in the list.
+    pub(super) fn matches(&self, element: &MemoryNode) -> bool {
+        self.0
+            .iter()
+            .any(|complex| complex.matches(element, complex.compounds.len() - 1))
+    }
+}
+
+impl Complex {
+    /// Match right-to-left, starting with the compound at `idx`.
+    fn matches(&self, element: &MemoryNode, idx: usize) -> bool {
+        if !self.compounds[idx]
+            .iter()
+            .all(|simple| simple.matches(element))
+        {
+            return false;
+        }
+        if idx == 0 {
+            return true;
+        }
+        match self.combinators[idx - 1] {
+            Combinator::Descendant => {
+                let mut ancestor = element.parent_element();
+                while let Some(node) = ancestor {
+                    if self.matches(&node, idx - 1) {
+                        return true;
+                    }
+                    ancestor = node.parent_element();
+                }
+                false
+            }
+            Combinator::Child => element
+                .parent_element()
+                .map_or(false, |parent| self.matches(&parent, idx - 1)),
+            Combinator::NextSibling => element
+                .previous_element_sibling()
+                .map_or(false, |sibling| self.matches(&sibling, idx - 1)),
+            Combinator::SubsequentSibling => {
+                let mut sibling = element.previous_element_sibling();
+                while let Some(node) = sibling {
+                    if self.matches(&node, idx - 1) {
+                        return true;
+                    }
+                    sibling = node.previous_element_sibling();
+                }
+                false
+            }
+        }
+    }
+}
+
+impl Simple {
+    fn matches(&self, element: &MemoryNode) -> bool {
+        match self {
+            Simple::Universal => element.is_element(),
+            Simple::Type(name) => element
+                .with_element(|data| data.local_name.eq_ignore_ascii_case(name))
+                .unwrap_or(false),
+            Simple::Id(id) => element.attribute("id").as_ref() == Some(id),
+            Simple::Class(class) => element.attribute("class").map_or(false, |classes| {
+                classes.split_whitespace().any(|c| c == class)
+            }),
+            Simple::Attribute(name, op) => {
+                let value = match element.attribute(name) {
+                    Some(value) => value,
+                    None => return false,
+                };
+                match op {
+                    None => true,
+                    Some((AttributeOp::Equals, expected)) => value == *expected,
+                    Some((AttributeOp::Includes, expected)) => {
+                        value.split_whitespace().any(|word| word == expected)
+                    }
+                    Some((AttributeOp::DashMatch, expected)) => {
+                        value == *expected || value.starts_with(&format!("{}-", expected))
+                    }
+                    Some((AttributeOp::Prefix, expected)) => {
+                        !expected.is_empty() && value.starts_with(expected.as_str())
+                    }
+                    Some((AttributeOp::Suffix, expected)) => {
+                        !expected.is_empty() && value.ends_with(expected.as_str())
+                    }
+                    Some((AttributeOp::Substring, expected)) => {
+                        !expected.is_empty() && value.contains(expected.as_str())
+                    }
+                }
+            }
+            Simple::FirstChild => element.previous_element_sibling().is_none(),
+            Simple::LastChild => element.next_element_sibling().is_none(),
+            Simple::OnlyChild => {
+                element.previous_element_sibling().is_none()
+                    && element.next_element_sibling().is_none()
+            }
+            Simple::Empty => element.children().iter().all(|child| {
+                !child.is_element() && child.text_data().map_or(true, |text| text.is_empty())
+            }),
+            Simple::Root => element
+                .parent()
+                .map_or(false, |parent| parent.is_document()),
+            Simple::Not(list) => !list.matches(element),
+        }
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    chars: Vec<char>,
+    pos: usize,
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_' || !c.is_ascii()
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self) -> DomException {
+        DomException::new(
+            DomExceptionKind::Syntax,
+            format!("'{}' is not a valid selector", self.input),
+        )
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).cloned()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), DomException> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+
+    /// Returns whether any whitespace was skipped.
+    fn skip_whitespace(&mut self) -> bool {
+        let start = self.pos;
+        while self.peek().map_or(false, char::is_whitespace) {
+            self.pos += 1;
+        }
+        self.pos > start
+    }
+
+    fn ident(&mut self) -> Result<String, DomException> {
+        let mut out = String::new();
+        while let Some(c) = self.peek() {
+            if c == '\\' {
+                self.pos += 1;
+                out.push(self.peek().ok_or_else(|| self.error())?);
+            } else if is_ident_char(c) {
+                out.push(c);
+            } else {
+                break;
+            }
+            self.pos += 1;
+        }
+        if out.is_empty() || out.starts_with(|c: char| c.is_ascii_digit()) {
+            return Err(self.error());
+        }
+        Ok(out)
+    }
+
+    fn string(&mut self) -> Result<String, DomException> {
+        let quote = match self.peek() {
+            Some(quote) if quote == '"' || quote == '\'' => quote,
+            _ => return self.ident(),
+        };
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error()),
+                Some(c) if c == quote => break,
+                Some('\\') => {
+                    self.pos += 1;
+                    out.push(self.peek().ok_or_else(|| self.error())?);
+                }
+                Some(c) => out.push(c),
+            }
+            self.pos += 1;
+        }
+        self.pos += 1;
+        Ok(out)
+    }
+
+    fn selector_list(&mut self) -> Result<SelectorList, DomException> {
+        let mut list = vec![self.complex()?];
+        while self.eat(',') {
+            list.push(self.complex()?);
+        }
+        Ok(SelectorList(list))
+    }
+
+    fn complex(&mut self) -> Result<Complex, DomException> {
+        self.skip_whitespace();
+        let mut compounds = vec![self.compound()?];
+        let mut combinators = Vec::new();
+        loop {
+            let whitespace = self.skip_whitespace();
+            let combinator = match self.peek() {
+                Some('>') => Combinator::Child,
+                Some('+') => Combinator::NextSibling,
+                Some('~') => Combinator::SubsequentSibling,
+                Some(',') | Some(')') | None => break,
+                _ if whitespace => Combinator::Descendant,
+                _ => return Err(self.error()),
+            };
+            if combinator != Combinator::Descendant {
+                self.pos += 1;
+                self.skip_whitespace();
+            }
+            combinators.push(combinator);
+            compounds.push(self.compound()?);
+        }
+        Ok(Complex {
+            compounds,
+            combinators,
+        })
+    }
+
+    fn compound(&mut self) -> Result<Vec<Simple>, DomException> {
+        let mut simple = Vec::new();
+        match self.peek() {
+            Some('*') => {
+                self.pos += 1;
+                simple.push(Simple::Universal);
+            }
+            Some(c) if is_ident_char(c) || c == '\\' => {
+                simple.push(Simple::Type(self.ident()?.to_ascii_lowercase()))
+            }
+            _ => (),
+        }
+        loop {
+            match self.peek() {
+                Some('#') => {
+                    self.pos += 1;
+                    simple.push(Simple::Id(self.ident()?));
+                }
+                Some('.') => {
+                    self.pos += 1;
+                    simple.push(Simple::Class(self.ident()?));
+                }
+                Some('[') => {
+                    self.pos += 1;
+                    simple.push(self.attribute()?);
+                }
+                Some(':') => {
+                    self.pos += 1;
+                    simple.push(self.pseudo_class()?);
+                }
+                _ => break,
+            }
+        }
+        if simple.is_empty() {
+            Err(self.error())
+        } else {
+            Ok(simple)
+        }
+    }
+
+    fn attribute(&mut self) -> Result<Simple, DomException> {
+        self.skip_whitespace();
+        let name = self.ident()?.to_ascii_lowercase();
+        self.skip_whitespace();
+        if self.eat(']') {
+            return Ok(Simple::Attribute(name, None));
+        }
+        let op = match self.peek() {
+            Some('=') => AttributeOp::Equals,
+            Some('~') => AttributeOp::Includes,
+            Some('|') => AttributeOp::DashMatch,
+            Some('^') => AttributeOp::Prefix,
+            Some('$') => AttributeOp::Suffix,
+            Some('*') => AttributeOp::Substring,
+            _ => return Err(self.error()),
+        };
+        self.pos += 1;
+        if op != AttributeOp::Equals {
+            self.expect('=')?;
+        }
+        self.skip_whitespace();
+        let value = self.string()?;
+        self.skip_whitespace();
+        self.expect(']')?;
+        Ok(Simple::Attribute(name, Some((op, value))))
+    }
+
+    fn pseudo_class(&mut self) -> Result<Simple, DomException> {
+        let name = self.ident()?.to_ascii_lowercase();
+        Ok(match name.as_str() {
+            "first-child" => Simple::FirstChild,
+            "last-child" => Simple::LastChild,
+            "only-child" => Simple::OnlyChild,
+            "empty" => Simple::Empty,
+            "root" => Simple::Root,
+            "not" => {
+                self.expect('(')?;
+                let list = self.selector_list()?;
+                self.skip_whitespace();
+                self.expect(')')?;
+                Simple::Not(list)
+            }
+            _ => return Err(self.error()),
+        })
+    }
+}
@@ -0,0 +1,258 @@
+//! The backends that the DOM wrappers in this crate delegate to.
+//!
+//! Every wrapper (`EventTarget`, `Node`, `Element`, `Document` and `Event`) holds a
+//! reference-counted handle into a backend. The `web` backend talks to the browser through
+//! `web_sys`, and the `memory` backend is a DOM written in pure rust, so code using this crate can
+//! run anywhere, including under `cargo test`.
+//!
+//! Which backend `dommer::document` and friends use is decided by `current`. Other backends can be
+//! plugged in by implementing `Backend`, `Handle` and `EventHandle`, and wrapping the handles
+//! with the `from_handle` constructors.
+
+use std::{any::Any, fmt, rc::Rc};
+
+use crate::{
+    dom_rect::DomRect,
+    element::InsertPosition,
+    error::{DomException, DomExceptionKind},
+    event::{AddEventListenerOptions, Event, EventData, EventInit, EventPhase},
+    node::{DocumentPosition, NodeType},
+};
+
+pub mod memory;
+pub mod web;
+
+/// A callback registered with `Handle::add_event_listener`.
+pub type Listener = Rc<dyn Fn(Event)>;
+
+/// A function that unregisters a listener when called.
+pub type Unsubscribe = Box<dyn FnMut()>;
+
+/// The backend used by default: `web` on wasm, `memory` everywhere else.
+pub fn current() -> &'static dyn Backend {
+    #[cfg(target_arch = "wasm32")]
+    {
+        &web::WebBackend
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        &memory::MemoryBackend
+    }
+}
+
+/// Entry points into a backend.
+pub trait Backend {
+    /// The document of the current page, if there is one.
+    fn document(&self) -> Option<Rc<dyn Handle>>;
+    /// Create a standalone event target (`new EventTarget()`).
+    fn create_event_target(&self) -> Result<Rc<dyn Handle>, DomException>;
+    /// Create an event that has not been dispatched yet (`new Event(kind, init)`), of the interface
+    /// matching `data`.
+    fn create_event(
+        &self,
+        kind: &str,
+        init: &EventInit,
+        data: &EventData,
+    ) -> Result<Rc<dyn EventHandle>, DomException>;
+}
+
+/// A handle to an object living in a backend.
+///
+/// The wrappers only call the methods that make sense for the kind of object they wrap, so for
+/// example the element methods will only ever be called on handles to elements. Methods that
+/// depend on layout have default implementations for backends that don't do layout.
+pub trait Handle: fmt::Debug {
+    fn as_any(&self) -> &dyn Any;
+
+    // EventTarget
+
+    fn add_event_listener(
+        &self,
+        kind: &str,
+        listener: Listener,
+        options: &AddEventListenerOptions,
+    ) -> Result<Unsubscribe, DomException>;
+    /// Whether `other` is a handle to the same object. Handles from other backends never are.
+    fn is_same_target(&self, other: &dyn Handle) -> bool;
+    fn dispatch_event(&self, event: &Event) -> Result<bool, DomException>;
+
+    // Node
+
+    /// Whether this is a node, rather than some other event target.
+    fn is_node(&self) -> bool;
+    fn node_type(&self) -> NodeType;
+    fn node_name(&self) -> String;
+    fn append_child(&self, child: &dyn Handle) -> Result<(), DomException>;
+    fn clone_node(&self, deep: bool) -> Result<Rc<dyn Handle>, DomException>;
+    fn compare_document_position(&self, other: &dyn Handle) -> DocumentPosition;
+    fn contains(&self, other: &dyn Handle) -> bool;
+    fn get_root_node(&self) -> Rc<dyn Handle>;
+    fn has_child_nodes(&self) -> bool;
+    fn insert_before(
+        &self,
+        node: &dyn Handle,
+        child: Option<&dyn Handle>,
+    ) -> Result<(), DomException>;
+    fn is_default_namespace(&self, namespace: Option<&str>) -> bool;
+    fn is_equal_node(&self, other: &dyn Handle) -> bool;
+    fn is_same_node(&self, other: &dyn Handle) -> bool;
+    fn lookup_namespace_uri(&self, prefix: Option<&str>) -> Option<String>;
+    fn lookup_prefix(&self, namespace: Option<&str>) -> Option<String>;
+    fn normalize(&self);
+    fn remove_child(&self, child: &dyn Handle) -> Result<Rc<dyn Handle>, DomException>;
+    fn replace_child(
+        &self,
+        node: &dyn Handle,
+        child: &dyn Handle,
+    ) -> Result<Rc<dyn Handle>, DomException>;
+    fn base_uri(&self) -> Result<Option<String>, DomException>;
+    fn is_connected(&self) -> bool;
+    fn parent_node(&self) -> Option<Rc<dyn Handle>>;
+    fn first_child(&self) -> Option<Rc<dyn Handle>>;
+    fn last_child(&self) -> Option<Rc<dyn Handle>>;
+    fn previous_sibling(&self) -> Option<Rc<dyn Handle>>;
+    fn next_sibling(&self) -> Option<Rc<dyn Handle>>;
+    fn node_value(&self) -> Option<String>;
+    fn set_node_value(&self, node_value: Option<&str>);
+    fn text_content(&self) -> Option<String>;
+    fn set_text_content(&self, text_content: Option<&str>);
+
+    // Element
+
+    fn closest(&self, selector: &str) -> Result<Option<Rc<dyn Handle>>, DomException>;
+    fn matches(&self, selector: &str) -> Result<bool, DomException>;
+    fn query_selector(&self, selectors: &str) -> Result<Option<Rc<dyn Handle>>, DomException>;
+    fn query_selector_all(&self, selectors: &str) -> Result<Vec<Rc<dyn Handle>>, DomException>;
+    fn get_attribute(&self, name: &str) -> Option<String>;
+    fn get_attribute_ns(&self, namespace: Option<&str>, local_name: &str) -> Option<String>;
+    fn has_attribute(&self, name: &str) -> bool;
+    fn has_attribute_ns(&self, namespace: Option<&str>, local_name: &str) -> bool;
+    fn has_attributes(&self) -> bool;
+    fn set_attribute(&self, name: &str, value: &str) -> Result<(), DomException>;
+    fn set_attribute_ns(
+        &self,
+        namespace: Option<&str>,
+        name: &str,
+        value: &str,
+    ) -> Result<(), DomException>;
+    fn remove_attribute(&self, name: &str) -> Result<(), DomException>;
+    fn remove_attribute_ns(
+        &self,
+        namespace: Option<&str>,
+        local_name: &str,
+    ) -> Result<(), DomException>;
+    fn toggle_attribute(&self, name: &str) -> Result<bool, DomException>;
+    fn insert_adjacent_element(
+        &self,
+        position: InsertPosition,
+        element: &dyn Handle,
+    ) -> Result<Option<Rc<dyn Handle>>, DomException>;
+    fn insert_adjacent_html(
+        &self,
+        position: InsertPosition,
+        data: &str,
+    ) -> Result<(), DomException>;
+    fn insert_adjacent_text(
+        &self,
+        position: InsertPosition,
+        text: &str,
+    ) -> Result<(), DomException>;
+    fn namespace_uri(&self) -> Option<String>;
+    fn prefix(&self) -> Option<String>;
+    fn local_name(&self) -> String;
+    fn tag_name(&self) -> String;
+    fn inner_html(&self) -> String;
+    fn set_inner_html(&self, inner_html: &str);
+    fn outer_html(&self) -> String;
+    fn set_outer_html(&self, outer_html: &str);
+    fn first_element_child(&self) -> Option<Rc<dyn Handle>>;
+    fn last_element_child(&self) -> Option<Rc<dyn Handle>>;
+    fn child_element_count(&self) -> u32;
+
+    // Element (layout and pointers)
+
+    fn get_bounding_client_rect(&self) -> DomRect {
+        DomRect {
+            x: 0.,
+            y: 0.,
+            width: 0.,
+            height: 0.,
+        }
+    }
+    fn get_client_rects(&self) -> Vec<DomRect> {
+        Vec::new()
+    }
+    fn has_pointer_capture(&self, _pointer_id: i32) -> bool {
+        false
+    }
+    fn set_pointer_capture(&self, pointer_id: i32) -> Result<(), DomException> {
+        Err(DomException::new(
+            DomExceptionKind::NotFound,
+            format!("no active pointer with the id {}", pointer_id),
+        ))
+    }
+    fn release_pointer_capture(&self, pointer_id: i32) -> Result<(), DomException> {
+        Err(DomException::new(
+            DomExceptionKind::NotFound,
+            format!("no active pointer with the id {}", pointer_id),
+        ))
+    }
+    fn request_fullscreen(&self) -> Result<(), DomException> {
+        Ok(())
+    }
+    fn scroll_top(&self) -> i32 {
+        0
+    }
+    fn set_scroll_top(&self, _scroll_top: i32) {}
+    fn scroll_left(&self) -> i32 {
+        0
+    }
+    fn set_scroll_left(&self, _scroll_left: i32) {}
+    fn scroll_width(&self) -> i32 {
+        0
+    }
+    fn scroll_height(&self) -> i32 {
+        0
+    }
+    fn client_top(&self) -> i32 {
+        0
+    }
+    fn client_left(&self) -> i32 {
+        0
+    }
+    fn client_width(&self) -> i32 {
+        0
+    }
+    fn client_height(&self) -> i32 {
+        0
+    }
+
+    // Document
+
+    fn body(&self) -> Option<Rc<dyn Handle>>;
+    fn create_element(&self, tag_name: &str) -> Result<Rc<dyn Handle>, DomException>;
+    fn create_text_node(&self, data: &str) -> Rc<dyn Handle>;
+}
+
+/// A handle to an event living in a backend.
+pub trait EventHandle: fmt::Debug {
+    fn as_any(&self) -> &dyn Any;
+
+    fn kind(&self) -> String;
+    fn bubbles(&self) -> bool;
+    fn cancelable(&self) -> bool;
+    fn composed(&self) -> bool;
+    /// The fields of the more specific interface the event implements, like `MouseEvent`.
+    fn data(&self) -> EventData;
+    fn is_trusted(&self) -> bool;
+    fn time_stamp(&self) -> f64;
+    fn event_phase(&self) -> EventPhase;
+    fn prevent_default(&self);
+    fn default_prevented(&self) -> bool;
+    fn stop_propagation(&self);
+    fn stop_immediate_propagation(&self);
+    fn target(&self) -> Option<Rc<dyn Handle>>;
+    fn current_target(&self) -> Option<Rc<dyn Handle>>;
+    /// The path of the current dispatch, starting at the target, or nothing outside of dispatch.
+    fn composed_path(&self) -> Vec<Rc<dyn Handle>>;
+}
@@ -0,0 +1,597 @@
+//! The browser backend, implemented with `web_sys`.
+
+use std::{any::Any, rc::Rc};
+use wasm_bindgen::{prelude::*, JsCast};
+
+use super::{Backend, EventHandle, Handle, Listener, Unsubscribe};
+use crate::{
+    dom_rect::DomRect,
+    element::InsertPosition,
+    error::DomException,
+    event::{
+        self,
+        custom::{self, PayloadGuard},
+        AddEventListenerOptions, Event, EventData, EventInit, EventPhase,
+    },
+    node::{DocumentPosition, NodeType},
+};
+
+/// The backend that talks to the browser.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct WebBackend;
+
+impl Backend for WebBackend {
+    fn document(&self) -> Option<Rc<dyn Handle>> {
+        let window = web_sys::window()?;
+        window.document().map(WebHandle::wrap)
+    }
+
+    fn create_event_target(&self) -> Result<Rc<dyn Handle>, DomException> {
+        web_sys::EventTarget::new()
+            .map(WebHandle::wrap)
+            .map_err(DomException::from_js)
+    }
+
+    fn create_event(
+        &self,
+        kind: &str,
+        init: &EventInit,
+        data: &EventData,
+    ) -> Result<Rc<dyn EventHandle>, DomException> {
+        event::new_web_sys(kind, init, data)
+            .map(|event| Rc::new(event) as Rc<dyn EventHandle>)
+            .map_err(DomException::from_js)
+    }
+}
+
+/// A handle to a `web_sys::EventTarget` (or one of its subclasses).
+#[derive(Debug, Clone)]
+pub struct WebHandle {
+    inner: web_sys::EventTarget,
+}
+
+impl WebHandle {
+    pub(crate) fn wrap(inner: impl Into<web_sys::EventTarget>) -> Rc<dyn Handle> {
+        Rc::new(WebHandle {
+            inner: inner.into(),
+        })
+    }
+
+    /// The underlying `web_sys` object.
+    pub fn as_web_sys(&self) -> &web_sys::EventTarget {
+        &self.inner
+    }
+
+    fn node(&self) -> &web_sys::Node {
+        self.inner.unchecked_ref()
+    }
+
+    fn element(&self) -> &web_sys::Element {
+        self.inner.unchecked_ref()
+    }
+
+    fn document(&self) -> &web_sys::Document {
+        self.inner.unchecked_ref()
+    }
+
+    fn of(handle: &dyn Handle) -> Option<&WebHandle> {
+        handle.as_any().downcast_ref::<WebHandle>()
+    }
+
+    fn node_of(handle: &dyn Handle) -> Result<&web_sys::Node, DomException> {
+        WebHandle::of(handle)
+            .map(WebHandle::node)
+            .ok_or_else(DomException::mixed_backends)
+    }
+}
+
+impl Handle for WebHandle {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn add_event_listener(
+        &self,
+        kind: &str,
+        listener: Listener,
+        options: &AddEventListenerOptions,
+    ) -> Result<Unsubscribe, DomException> {
+        let target = self.inner.clone();
+        let closure = Closure::wrap(Box::new(move |event: web_sys::Event| {
+            listener(event.into());
+        }) as Box<dyn Fn(web_sys::Event)>);
+        let kind = kind.to_owned();
+        if let Some(opts) = options.into_web_sys() {
+            target
+                .add_event_listener_with_callback_and_add_event_listener_options(
+                    &kind,
+                    closure.as_ref().unchecked_ref(),
+                    &opts,
+                )
+                .map_err(DomException::from_js)?;
+        } else {
+            target
+                .add_event_listener_with_callback(&kind, closure.as_ref().unchecked_ref())
+                .map_err(DomException::from_js)?;
+        }
+        if let Some(opts) = options.into_web_sys_remove() {
+            Ok(Box::new(move || {
+                expect!(
+                    target.remove_event_listener_with_callback_and_event_listener_options(
+                        &kind,
+                        closure.as_ref().unchecked_ref(),
+                        &opts
+                    ),
+                    "removing event listener"
+                );
+            }))
+        } else {
+            Ok(Box::new(move || {
+                expect!(
+                    target.remove_event_listener_with_callback(
+                        &kind,
+                        closure.as_ref().unchecked_ref()
+                    ),
+                    "removing event listener"
+                );
+            }))
+        }
+    }
+
+    fn is_same_target(&self, other: &dyn Handle) -> bool {
+        match other.as_any().downcast_ref::<WebHandle>() {
+            Some(other) => self.inner == other.inner,
+            None => false,
+        }
+    }
+
+    fn dispatch_event(&self, event: &Event) -> Result<bool, DomException> {
+        match event.as_web_sys() {
+            Some(event) => self.inner.dispatch_event(event),
+            None => {
+                // The event comes from another backend, so make a browser copy of it.
+                let mut init = EventInit::default();
+                init.bubbles(event.raw.bubbles());
+                init.cancelable(event.raw.cancelable());
+                init.composed(event.raw.composed());
+                let copy = event::new_web_sys(&event.raw.kind(), &init, &event.raw.data())
+                    .map_err(DomException::from_js)?;
+                self.inner.dispatch_event(&copy.inner)
+            }
+        }
+        .map_err(DomException::from_js)
+    }
+
+    fn is_node(&self) -> bool {
+        self.inner.is_instance_of::<web_sys::Node>()
+    }
+    fn node_type(&self) -> NodeType {
+        NodeType::from_web_sys(self.node().node_type())
+    }
+    fn node_name(&self) -> String {
+        self.node().node_name()
+    }
+    fn append_child(&self, child: &dyn Handle) -> Result<(), DomException> {
+        self.node()
+            .append_child(WebHandle::node_of(child)?)
+            .map(|_| ())
+            .map_err(DomException::from_js)
+    }
+    fn clone_node(&self, deep: bool) -> Result<Rc<dyn Handle>, DomException> {
+        self.node()
+            .clone_node_with_deep(deep)
+            .map(WebHandle::wrap)
+            .map_err(DomException::from_js)
+    }
+    fn compare_document_position(&self, other: &dyn Handle) -> DocumentPosition {
+        match WebHandle::of(other) {
+            Some(other) => DocumentPosition::from_bits_truncate(
+                self.node().compare_document_position(other.node()),
+            ),
+            None => DocumentPosition::DISCONNECTED | DocumentPosition::IMPLEMENTATION_SPECIFIC,
+        }
+    }
+    fn contains(&self, other: &dyn Handle) -> bool {
+        match WebHandle::of(other) {
+            Some(other) => self.node().contains(Some(other.node())),
+            None => false,
+        }
+    }
+    fn get_root_node(&self) -> Rc<dyn Handle> {
+        WebHandle::wrap(self.node().get_root_node())
+    }
+    fn has_child_nodes(&self) -> bool {
+        self.node().has_child_nodes()
+    }
+    fn insert_before(
+        &self,
+        node: &dyn Handle,
+        child: Option<&dyn Handle>,
+    ) -> Result<(), DomException> {
+        let child = match child {
+            Some(child) => Some(WebHandle::node_of(child)?),
+            None => None,
+        };
+        self.node()
+            .insert_before(WebHandle::node_of(node)?, child)
+            .map(|_| ())
+            .map_err(DomException::from_js)
+    }
+    fn is_default_namespace(&self, namespace: Option<&str>) -> bool {
+        self.node().is_default_namespace(namespace)
+    }
+    fn is_equal_node(&self, other: &dyn Handle) -> bool {
+        match WebHandle::of(other) {
+            Some(other) => self.node().is_equal_node(Some(other.node())),
+            None => false,
+        }
+    }
+    fn is_same_node(&self, other: &dyn Handle) -> bool {
+        match WebHandle::of(other) {
+            Some(other) => self.node().is_same_node(Some(other.node())),
+            None => false,
+        }
+    }
+    fn lookup_namespace_uri(&self, prefix: Option<&str>) -> Option<String> {
+        self.node().lookup_namespace_uri(prefix)
+    }
+    fn lookup_prefix(&self, namespace: Option<&str>) -> Option<String> {
+        self.node().lookup_prefix(namespace)
+    }
+    fn normalize(&self) {
+        self.node().normalize()
+    }
+    fn remove_child(&self, child: &dyn Handle) -> Result<Rc<dyn Handle>, DomException> {
+        self.node()
+            .remove_child(WebHandle::node_of(child)?)
+            .map(WebHandle::wrap)
+            .map_err(DomException::from_js)
+    }
+    fn replace_child(
+        &self,
+        node: &dyn Handle,
+        child: &dyn Handle,
+    ) -> Result<Rc<dyn Handle>, DomException> {
+        self.node()
+            .replace_child(WebHandle::node_of(node)?, WebHandle::node_of(child)?)
+            .map(WebHandle::wrap)
+            .map_err(DomException::from_js)
+    }
+    fn base_uri(&self) -> Result<Option<String>, DomException> {
+        self.node().base_uri().map_err(DomException::from_js)
+    }
+    fn is_connected(&self) -> bool {
+        self.node().is_connected()
+    }
+    fn parent_node(&self) -> Option<Rc<dyn Handle>> {
+        self.node().parent_node().map(WebHandle::wrap)
+    }
+    fn first_child(&self) -> Option<Rc<dyn Handle>> {
+        self.node().first_child().map(WebHandle::wrap)
+    }
+    fn last_child(&self) -> Option<Rc<dyn Handle>> {
+        self.node().last_child().map(WebHandle::wrap)
+    }
+    fn previous_sibling(&self) -> Option<Rc<dyn Handle>> {
+        self.node().previous_sibling().map(WebHandle::wrap)
+    }
+    fn next_sibling(&self) -> Option<Rc<dyn Handle>> {
+        self.node().next_sibling().map(WebHandle::wrap)
+    }
+    fn node_value(&self) -> Option<String> {
+        self.node().node_value()
+    }
+    fn set_node_value(&self, node_value: Option<&str>) {
+        self.node().set_node_value(node_value)
+    }
+    fn text_content(&self) -> Option<String> {
+        self.node().text_content()
+    }
+    fn set_text_content(&self, text_content: Option<&str>) {
+        self.node().set_text_content(text_content)
+    }
+
+    fn closest(&self, selector: &str) -> Result<Option<Rc<dyn Handle>>, DomException> {
+        self.element()
+            .closest(selector)
+            .map(|opt| opt.map(WebHandle::wrap))
+            .map_err(DomException::from_js)
+    }
+    fn matches(&self, selector: &str) -> Result<bool, DomException> {
+        self.element()
+            .matches(selector)
+            .map_err(DomException::from_js)
+    }
+    fn query_selector(&self, selectors: &str) -> Result<Option<Rc<dyn Handle>>, DomException> {
+        self.element()
+            .query_selector(selectors)
+            .map(|opt| opt.map(WebHandle::wrap))
+            .map_err(DomException::from_js)
+    }
+    fn query_selector_all(&self, selectors: &str) -> Result<Vec<Rc<dyn Handle>>, DomException> {
+        let list = self
+            .element()
+            .query_selector_all(selectors)
+            .map_err(DomException::from_js)?;
+        Ok((0..list.length())
+            .filter_map(|idx| list.get(idx))
+            .map(WebHandle::wrap)
+            .collect())
+    }
+    fn get_attribute(&self, name: &str) -> Option<String> {
+        self.element().get_attribute(name)
+    }
+    fn get_attribute_ns(&self, namespace: Option<&str>, local_name: &str) -> Option<String> {
+        self.element().get_attribute_ns(namespace, local_name)
+    }
+    fn has_attribute(&self, name: &str) -> bool {
+        self.element().has_attribute(name)
+    }
+    fn has_attribute_ns(&self, namespace: Option<&str>, local_name: &str) -> bool {
+        self.element().has_attribute_ns(namespace, local_name)
+    }
+    fn has_attributes(&self) -> bool {
+        self.element().has_attributes()
+    }
+    fn set_attribute(&self, name: &str, value: &str) -> Result<(), DomException> {
+        self.element()
+            .set_attribute(name, value)
+            .map_err(DomException::from_js)
+    }
+    fn set_attribute_ns(
+        &self,
+        namespace: Option<&str>,
+        name: &str,
+        value: &str,
+    ) -> Result<(), DomException> {
+        self.element()
+            .set_attribute_ns(namespace, name, value)
+            .map_err(DomException::from_js)
+    }
+    fn remove_attribute(&self, name: &str) -> Result<(), DomException> {
+        self.element()
+            .remove_attribute(name)
+            .map_err(DomException::from_js)
+    }
+    fn remove_attribute_ns(
+        &self,
+        namespace: Option<&str>,
+        local_name: &str,
+    ) -> Result<(), DomException> {
+        self.element()
+            .remove_attribute_ns(namespace, local_name)
+            .map_err(DomException::from_js)
+    }
+    fn toggle_attribute(&self, name: &str) -> Result<bool, DomException> {
+        self.element()
+            .toggle_attribute(name)
+            .map_err(DomException::from_js)
+    }
+    fn insert_adjacent_element(
+        &self,
+        position: InsertPosition,
+        element: &dyn Handle,
+    ) -> Result<Option<Rc<dyn Handle>>, DomException> {
+        let element = WebHandle::of(element).ok_or_else(DomException::mixed_backends)?;
+        self.element()
+            .insert_adjacent_element(position.as_web_sys(), element.element())
+            .map(|opt| opt.map(WebHandle::wrap))
+            .map_err(DomException::from_js)
+    }
+    fn insert_adjacent_html(
+        &self,
+        position: InsertPosition,
+        data: &str,
+    ) -> Result<(), DomException> {
+        self.element()
+            .insert_adjacent_html(position.as_web_sys(), data)
+            .map_err(DomException::from_js)
+    }
+    fn insert_adjacent_text(
+        &self,
+        position: InsertPosition,
+        text: &str,
+    ) -> Result<(), DomException> {
+        self.element()
+            .insert_adjacent_text(position.as_web_sys(), text)
+            .map_err(DomException::from_js)
+    }
+    fn namespace_uri(&self) -> Option<String> {
+        self.element().namespace_uri()
+    }
+    fn prefix(&self) -> Option<String> {
+        self.element().prefix()
+    }
+    fn local_name(&self) -> String {
+        self.element().local_name()
+    }
+    fn tag_name(&self) -> String {
+        self.element().tag_name()
+    }
+    fn inner_html(&self) -> String {
+        self.element().inner_html()
+    }
+    fn set_inner_html(&self, inner_html: &str) {
+        self.element().set_inner_html(inner_html)
+    }
+    fn outer_html(&self) -> String {
+        self.element().outer_html()
+    }
+    fn set_outer_html(&self, outer_html: &str) {
+        self.element().set_outer_html(outer_html)
+    }
+    fn first_element_child(&self) -> Option<Rc<dyn Handle>> {
+        self.element().first_element_child().map(WebHandle::wrap)
+    }
+    fn last_element_child(&self) -> Option<Rc<dyn Handle>> {
+        self.element().last_element_child().map(WebHandle::wrap)
+    }
+    fn child_element_count(&self) -> u32 {
+        self.element().child_element_count()
+    }
+
+    fn get_bounding_client_rect(&self) -> DomRect {
+        DomRect::from_web_sys(self.element().get_bounding_client_rect())
+    }
+    // todo consider if this is to expensive - maybe we should wrap web_sys::DomRect and
+    // provide an iterator?
+    fn get_client_rects(&self) -> Vec<DomRect> {
+        let raw = self.element().get_client_rects();
+        let length = raw.length();
+        let mut out = Vec::with_capacity(length as usize);
+        for i in 0..length {
+            out.push(DomRect::from_web_sys(expect_opt!(
+                raw.item(i),
+                "out of bounds error calling DomRectList::item"
+            )));
+        }
+        out
+    }
+    fn has_pointer_capture(&self, pointer_id: i32) -> bool {
+        self.element().has_pointer_capture(pointer_id)
+    }
+    fn set_pointer_capture(&self, pointer_id: i32) -> Result<(), DomException> {
+        self.element()
+            .set_pointer_capture(pointer_id)
+            .map_err(DomException::from_js)
+    }
+    fn release_pointer_capture(&self, pointer_id: i32) -> Result<(), DomException> {
+        self.element()
+            .release_pointer_capture(pointer_id)
+            .map_err(DomException::from_js)
+    }
+    fn request_fullscreen(&self) -> Result<(), DomException> {
+        self.element()
+            .request_fullscreen()
+            .map_err(DomException::from_js)
+    }
+    fn scroll_top(&self) -> i32 {
+        self.element().scroll_top()
+    }
+    fn set_scroll_top(&self, scroll_top: i32) {
+        self.element().set_scroll_top(scroll_top)
+    }
+    fn scroll_left(&self) -> i32 {
+        self.element().scroll_left()
+    }
+    fn set_scroll_left(&self, scroll_left: i32) {
+        self.element().set_scroll_left(scroll_left)
+    }
+    fn scroll_width(&self) -> i32 {
+        self.element().scroll_width()
+    }
+    fn scroll_height(&self) -> i32 {
+        self.element().scroll_height()
+    }
+    fn client_top(&self) -> i32 {
+        self.element().client_top()
+    }
+    fn client_left(&self) -> i32 {
+        self.element().client_left()
+    }
+    fn client_width(&self) -> i32 {
+        self.element().client_width()
+    }
+    fn client_height(&self) -> i32 {
+        self.element().client_height()
+    }
+
+    fn body(&self) -> Option<Rc<dyn Handle>> {
+        self.document().body().map(WebHandle::wrap)
+    }
+    fn create_element(&self, tag_name: &str) -> Result<Rc<dyn Handle>, DomException> {
+        self.document()
+            .create_element(tag_name)
+            .map(WebHandle::wrap)
+            .map_err(DomException::from_js)
+    }
+    fn create_text_node(&self, data: &str) -> Rc<dyn Handle> {
+        WebHandle::wrap(self.document().create_text_node(data))
+    }
+}
+
+/// A handle to a `web_sys::Event`.
+#[derive(Debug, Clone)]
+pub struct WebEvent {
+    pub(crate) inner: web_sys::Event,
+    // The rust detail of a `CustomEvent`, looked up once so it outlives the dispatched event.
+    pub(crate) detail: Option<Rc<dyn Any>>,
+    // Keeps the detail of a `CustomEvent` created in rust where the browser's copy can find it.
+    pub(crate) _payload: Option<Rc<PayloadGuard>>,
+}
+
+impl WebEvent {
+    /// Wrap an event from the browser, e.g. one passed to a listener.
+    pub(crate) fn new(inner: web_sys::Event) -> WebEvent {
+        // The registry only has the detail while the dispatched `CustomEvent` is alive, so it has
+        // to be found now rather than when a listener downcasts.
+        let detail = inner
+            .dyn_ref::<web_sys::CustomEvent>()
+            .and_then(|event| custom::lookup(&event.detail()));
+        WebEvent {
+            inner,
+            detail,
+            _payload: None,
+        }
+    }
+}
+
+impl EventHandle for WebEvent {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn kind(&self) -> String {
+        self.inner.type_()
+    }
+    fn bubbles(&self) -> bool {
+        self.inner.bubbles()
+    }
+    fn cancelable(&self) -> bool {
+        self.inner.cancelable()
+    }
+    fn composed(&self) -> bool {
+        self.inner.composed()
+    }
+    fn data(&self) -> EventData {
+        match &self.detail {
+            Some(detail) => EventData::Custom(detail.clone()),
+            None => event::data_from_web_sys(&self.inner),
+        }
+    }
+    fn is_trusted(&self) -> bool {
+        self.inner.is_trusted()
+    }
+    fn time_stamp(&self) -> f64 {
+        self.inner.time_stamp()
+    }
+    fn event_phase(&self) -> EventPhase {
+        EventPhase::from_web_sys(self.inner.event_phase())
+    }
+    fn prevent_default(&self) {
+        self.inner.prevent_default()
+    }
+    fn default_prevented(&self) -> bool {
+        self.inner.default_prevented()
+    }
+    fn stop_propagation(&self) {
+        self.inner.stop_propagation()
+    }
+    fn stop_immediate_propagation(&self) {
+        self.inner.stop_immediate_propagation()
+    }
+    fn target(&self) -> Option<Rc<dyn Handle>> {
+        self.inner.target().map(WebHandle::wrap)
+    }
+    fn current_target(&self) -> Option<Rc<dyn Handle>> {
+        self.inner.current_target().map(WebHandle::wrap)
+    }
+    fn composed_path(&self) -> Vec<Rc<dyn Handle>> {
+        self.inner
+            .composed_path()
+            .iter()
+            .filter_map(|target| target.dyn_into::<web_sys::EventTarget>().ok())
+            .map(WebHandle::wrap)
+            .collect()
+    }
+}
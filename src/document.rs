@@ -1,33 +1,56 @@
-use crate::{element, node};
+use std::rc::Rc;
 
-#[repr(transparent)]
+use crate::{backend::Handle, element, error::DomException, node};
+
+#[derive(Debug, Clone)]
 pub struct Document {
-    inner: web_sys::Document,
+    node: node::Node,
+}
+
+impl std::ops::Deref for Document {
+    type Target = node::Node;
+
+    fn deref(&self) -> &Self::Target {
+        &self.node
+    }
+}
+
+impl From<Document> for node::Node {
+    fn from(document: Document) -> node::Node {
+        document.node
+    }
 }
 
 impl Document {
-    pub(crate) fn from_web_sys(inner: web_sys::Document) -> Document {
-        Document { inner }
+    /// Wrap a handle to a document from any backend.
+    pub fn from_handle(raw: Rc<dyn Handle>) -> Document {
+        Document {
+            node: node::Node::from_handle(raw),
+        }
     }
 
+    /// Panics if there is no `<body>` yet, see `try_body`.
     pub fn body(&self) -> element::Element {
-        expect_opt!(
-            self.inner
-                .body()
-                .map(|html_el| element::Element::from_web_sys(html_el.into())),
-            "Document::body returned None"
-        )
+        expect_opt!(self.try_body(), "Document::body returned None")
+    }
+    /// The `<body>` element, which is `None` until the document has been parsed that far (e.g.
+    /// in a script in the `<head>`).
+    pub fn try_body(&self) -> Option<element::Element> {
+        self.raw().body().map(element::Element::from_handle)
     }
     pub fn create_element(&self, tag_name: &str) -> element::Element {
         expect!(
-            self.inner
-                .create_element(tag_name)
-                .map(element::Element::from_web_sys),
+            self.try_create_element(tag_name),
             "calling Document::create_element"
         )
     }
+    /// Fails with an `InvalidCharacterError` if `tag_name` is not a valid name.
+    pub fn try_create_element(&self, tag_name: &str) -> Result<element::Element, DomException> {
+        self.raw()
+            .create_element(tag_name)
+            .map(element::Element::from_handle)
+    }
     pub fn create_text_node(&self, data: &str) -> node::Node {
-            let raw = self.inner.create_text_node(data);
-            node::Node::from_web_sys(raw.into())
+        node::Node::from_handle(self.raw().create_text_node(data))
     }
 }
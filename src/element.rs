@@ -1,9 +1,6 @@
-use crate::{dom_rect::DomRect, node};
-//use wasm_bindgen::prelude::*;
+use std::{convert::TryFrom, rc::Rc};
 
-pub struct InvalidSelector;
-pub struct InvalidPointerId;
-pub struct InvalidCharacter;
+use crate::{backend::Handle, dom_rect::DomRect, error::DomException, event, node};
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum InsertPosition {
@@ -24,244 +21,374 @@ impl InsertPosition {
     }
 }
 
-#[repr(transparent)]
 #[derive(Debug, Clone)]
 pub struct Element {
-    inner: web_sys::Element,
+    node: node::Node,
 }
 
 impl std::ops::Deref for Element {
     type Target = node::Node;
 
     fn deref(&self) -> &Self::Target {
-        unsafe { std::mem::transmute::<&web_sys::Node, &node::Node>(&*self.inner) }
+        &self.node
+    }
+}
+
+impl From<Element> for node::Node {
+    fn from(element: Element) -> node::Node {
+        element.node
+    }
+}
+
+/// Gives the node back if it isn't an element.
+impl TryFrom<node::Node> for Element {
+    type Error = node::Node;
+
+    fn try_from(node: node::Node) -> Result<Element, node::Node> {
+        if node.node_type() == node::NodeType::Element {
+            Ok(Element { node })
+        } else {
+            Err(node)
+        }
+    }
+}
+
+/// Gives the target back if it isn't an element.
+impl TryFrom<event::EventTarget> for Element {
+    type Error = event::EventTarget;
+
+    fn try_from(target: event::EventTarget) -> Result<Element, event::EventTarget> {
+        Element::try_from(node::Node::try_from(target)?).map_err(event::EventTarget::from)
     }
 }
 
 impl Element {
-    pub(crate) fn from_web_sys(inner: web_sys::Element) -> Self {
-        Element { inner }
+    /// Wrap a handle to an element from any backend.
+    pub fn from_handle(raw: Rc<dyn Handle>) -> Self {
+        Element {
+            node: node::Node::from_handle(raw),
+        }
     }
-    pub fn closest(&self, selector: &str) -> Result<Option<Element>, InvalidSelector> {
-        self.inner
+    /// Run `handler` when an event of `event_kind` fires on a descendant of this element that
+    /// matches `selector`, or on something inside such a descendant. This uses a single listener
+    /// on this element, however many descendants there are.
+    ///
+    /// The handler is passed the matching element nearest the target.
+    ///
+    /// An invalid `selector` is rejected straight away with a `DomException` of kind
+    /// `DomExceptionKind::Syntax`.
+    ///
+    /// ```rust,no_run
+    /// use dommer::event::EventKind;
+    ///
+    /// let table = dommer::document().create_element("table");
+    /// let _guard = table
+    ///     .delegate(EventKind::Click, "tr[data-id]", |_event, row| {
+    ///         dommer::println!("clicked row {:?}", row.get_attribute("data-id"));
+    ///     })
+    ///     .unwrap();
+    /// ```
+    pub fn delegate(
+        &self,
+        event_kind: event::EventKind,
+        selector: &str,
+        handler: impl Fn(event::Event, Element) + 'static,
+    ) -> Result<event::SubscribeGuard, DomException> {
+        // Check the selector now, rather than in every event.
+        self.matches(selector)?;
+        let selector = selector.to_owned();
+        Ok(self.add_event_listener(event_kind, move |event| {
+            // The listener doesn't hold on to this element, so it doesn't keep itself alive.
+            let container = event.current_target().map(Element::try_from);
+            let target = event.target().map(Element::try_from);
+            let (container, target) = match (container, target) {
+                (Some(Ok(container)), Some(Ok(target))) => (container, target),
+                _ => return,
+            };
+            let matched = match target.closest(&selector) {
+                Ok(Some(matched)) => matched,
+                _ => return,
+            };
+            if !matched.is_same_node(container.clone()) && container.contains(matched.clone()) {
+                handler(event, matched);
+            }
+        }))
+    }
+    pub fn closest(&self, selector: &str) -> Result<Option<Element>, DomException> {
+        self.raw()
             .closest(selector)
-            .map(|opt| opt.map(Element::from_web_sys))
-            .map_err(|_| InvalidSelector)
+            .map(|opt| opt.map(Element::from_handle))
     }
     pub fn get_attribute(&self, name: &str) -> Option<String> {
-        self.inner.get_attribute(name)
+        self.raw().get_attribute(name)
     }
     pub fn get_attribute_ns(&self, namespace: Option<&str>, local_name: &str) -> Option<String> {
-        self.inner.get_attribute_ns(namespace, local_name)
+        self.raw().get_attribute_ns(namespace, local_name)
     }
     pub fn get_bounding_client_rect(&self) -> DomRect {
-        DomRect::from_web_sys(self.inner.get_bounding_client_rect())
+        self.raw().get_bounding_client_rect()
     }
-    // todo consider if this is to expensive - maybe we should wrap web_sys::DomRect and
-    // provide an iterator?
     pub fn get_client_rects(&self) -> Vec<DomRect> {
-        let raw = self.inner.get_client_rects();
-        let length = raw.length();
-        let mut out = Vec::with_capacity(length as usize);
-        for i in 0..length {
-            out.push(DomRect::from_web_sys(expect_opt!(
-                raw.item(i),
-                "out of bounds error calling DomRectList::item"
-            )));
-        }
-        out
+        self.raw().get_client_rects()
     }
     pub fn has_attribute(&self, name: &str) -> bool {
-        self.inner.has_attribute(name)
+        self.raw().has_attribute(name)
     }
     pub fn has_attribute_ns(&self, namespace: Option<&str>, local_name: &str) -> bool {
-        self.inner.has_attribute_ns(namespace, local_name)
+        self.raw().has_attribute_ns(namespace, local_name)
     }
     pub fn has_attributes(&self) -> bool {
-        self.inner.has_attributes()
+        self.raw().has_attributes()
     }
     pub fn has_pointer_capture(&self, pointer_id: i32) -> bool {
-        self.inner.has_pointer_capture(pointer_id)
+        self.raw().has_pointer_capture(pointer_id)
     }
 
-    // The following function should not error as we control the types
+    /// Returns `None` if the position is outside `self` and `self` has no parent. Panics if
+    /// `element` can't go there, see `try_insert_adjacent_element`.
     pub fn insert_adjacent_element(
         &self,
         position: InsertPosition,
         element: impl Into<Element>,
     ) -> Option<Element> {
-        let res = expect!(
-            self.inner
-                .insert_adjacent_element(position.as_web_sys(), &element.into().inner),
+        expect!(
+            self.try_insert_adjacent_element(position, element),
             "calling Element::insert_adjacent_element"
-        );
-        res.map(Element::from_web_sys)
+        )
+    }
+    /// Fails with a `HierarchyRequestError` if `element` is an ancestor of `self`, or can't be
+    /// inserted at `position`.
+    pub fn try_insert_adjacent_element(
+        &self,
+        position: InsertPosition,
+        element: impl Into<Element>,
+    ) -> Result<Option<Element>, DomException> {
+        self.raw()
+            .insert_adjacent_element(position, element.into().raw())
+            .map(|opt| opt.map(Element::from_handle))
     }
-    // todo check the following doesn't throw
     pub fn insert_adjacent_html(&self, position: InsertPosition, data: &str) {
         expect!(
-            self.inner.insert_adjacent_html(position.as_web_sys(), data),
+            self.try_insert_adjacent_html(position, data),
             "calling Element::insert_adjacent_html"
         )
     }
-    // todo check the following doesn't throw
+    /// Fails with a `NoModificationAllowedError` when inserting before or after an element that
+    /// has no parent element (e.g. the `<html>` element).
+    pub fn try_insert_adjacent_html(
+        &self,
+        position: InsertPosition,
+        data: &str,
+    ) -> Result<(), DomException> {
+        self.raw().insert_adjacent_html(position, data)
+    }
     pub fn insert_adjacent_text(&self, position: InsertPosition, text: &str) {
         expect!(
-            self.inner.insert_adjacent_text(position.as_web_sys(), text),
+            self.try_insert_adjacent_text(position, text),
             "calling Element::insert_adjacent_text"
         )
     }
-    pub fn matches(&self, selector: &str) -> Result<bool, InvalidSelector> {
-        self.inner.matches(selector).map_err(|_| InvalidSelector)
+    /// Fails with a `HierarchyRequestError` when inserting text directly into a document.
+    pub fn try_insert_adjacent_text(
+        &self,
+        position: InsertPosition,
+        text: &str,
+    ) -> Result<(), DomException> {
+        self.raw().insert_adjacent_text(position, text)
+    }
+    pub fn matches(&self, selector: &str) -> Result<bool, DomException> {
+        self.raw().matches(selector)
     }
     // warning - this function walks the whole DOM tree even if it is called on a branch.
-    pub fn query_selector(&self, selectors: &str) -> Result<Option<Element>, InvalidSelector> {
-        self.inner
+    pub fn query_selector(&self, selectors: &str) -> Result<Option<Element>, DomException> {
+        self.raw()
             .query_selector(selectors)
-            .map(|opt| opt.map(Element::from_web_sys))
-            .map_err(|_| InvalidSelector)
+            .map(|opt| opt.map(Element::from_handle))
     }
-    pub fn query_selector_all(&self, selectors: &str) -> Result<node::NodeList, InvalidSelector> {
-        self.inner
+    pub fn query_selector_all(&self, selectors: &str) -> Result<node::NodeList, DomException> {
+        self.raw()
             .query_selector_all(selectors)
-            .map(node::NodeList::from_web_sys)
-            .map_err(|_| InvalidSelector)
+            .map(node::NodeList::from_handles)
     }
     //pub fn release_capture(&self); // I think this isn't really used (point_capture)
-    pub fn release_pointer_capture(&self, pointer_id: i32) -> Result<(), InvalidPointerId> {
-        self.inner
-            .release_pointer_capture(pointer_id)
-            .map_err(|_| InvalidPointerId)
+    pub fn release_pointer_capture(&self, pointer_id: i32) -> Result<(), DomException> {
+        self.raw().release_pointer_capture(pointer_id)
     }
     // todo mdn says this doesn't throw - is this right.
     pub fn remove_attribute(&self, name: &str) {
         expect!(
-            self.inner.remove_attribute(name),
+            self.try_remove_attribute(name),
             "calling Element::remove_attribute"
         )
     }
+    pub fn try_remove_attribute(&self, name: &str) -> Result<(), DomException> {
+        self.raw().remove_attribute(name)
+    }
     pub fn remove_attribute_ns(&self, namespace: Option<&str>, local_name: &str) {
         expect!(
-            self.inner.remove_attribute_ns(namespace, local_name),
+            self.try_remove_attribute_ns(namespace, local_name),
             "calling Element::remove_attribute_ns"
         )
     }
+    pub fn try_remove_attribute_ns(
+        &self,
+        namespace: Option<&str>,
+        local_name: &str,
+    ) -> Result<(), DomException> {
+        self.raw().remove_attribute_ns(namespace, local_name)
+    }
     // todo I tried to play with this and the console didn't show it. I don't think it can throw -
     // it fires an event on error.
     pub fn request_fullscreen(&self) {
         expect!(
-            self.inner.request_fullscreen(),
+            self.try_request_fullscreen(),
             "calling Element::request_fullscreen"
         )
     }
+    pub fn try_request_fullscreen(&self) -> Result<(), DomException> {
+        self.raw().request_fullscreen()
+    }
     //pub fn request_pointer_lock(&self); // This is experimental
-    pub fn set_attribute(&self, name: &str, value: &str) -> Result<(), InvalidCharacter> {
-        self.inner
-            .set_attribute(name, value)
-            .map_err(|_| InvalidCharacter)
+    pub fn set_attribute(&self, name: &str, value: &str) -> Result<(), DomException> {
+        self.raw().set_attribute(name, value)
     }
     pub fn set_attribute_ns(
         &self,
         namespace: Option<&str>,
         local_name: &str,
         value: &str,
-    ) -> Result<(), InvalidCharacter> {
-        self.inner
-            .set_attribute_ns(namespace, local_name, value)
-            .map_err(|_| InvalidCharacter)
+    ) -> Result<(), DomException> {
+        self.raw().set_attribute_ns(namespace, local_name, value)
     }
-    pub fn set_pointer_capture(&self, pointer_id: i32) -> Result<(), InvalidPointerId> {
-        self.inner
-            .set_pointer_capture(pointer_id)
-            .map_err(|_| InvalidPointerId)
+    pub fn set_pointer_capture(&self, pointer_id: i32) -> Result<(), DomException> {
+        self.raw().set_pointer_capture(pointer_id)
     }
     // todo is `force` desirable/useful?
-    pub fn toggle_attribute(&self, name: &str) -> Result<bool, InvalidCharacter> {
-        self.inner
-            .toggle_attribute(name)
-            .map_err(|_| InvalidCharacter)
+    pub fn toggle_attribute(&self, name: &str) -> Result<bool, DomException> {
+        self.raw().toggle_attribute(name)
     }
     pub fn namespace_uri(&self) -> Option<String> {
-        self.inner.namespace_uri()
+        self.raw().namespace_uri()
     }
     pub fn prefix(&self) -> Option<String> {
-        self.inner.prefix()
+        self.raw().prefix()
     }
     pub fn local_name(&self) -> String {
-        self.inner.local_name()
+        self.raw().local_name()
     }
     pub fn tag_name(&self) -> String {
-        self.inner.tag_name()
+        self.raw().tag_name()
     }
     pub fn id(&self) -> String {
-        self.inner.id()
+        self.get_attribute("id").unwrap_or_default()
     }
     pub fn set_id(&self, id: &str) {
-        self.inner.set_id(id)
+        expect!(
+            self.raw().set_attribute("id", id),
+            "calling Element::set_id"
+        )
     }
     pub fn class_name(&self) -> String {
-        self.inner.class_name()
+        self.get_attribute("class").unwrap_or_default()
     }
     pub fn set_class_name(&self, class_name: &str) {
-        self.inner.set_class_name(class_name)
+        expect!(
+            self.raw().set_attribute("class", class_name),
+            "calling Element::set_class_name"
+        )
     }
     // TODO
     //pub fn class_list(&self) -> DomTokenList;
     //pub fn attributes(&self) -> NamedNodeMap;
     // todo mdn says this should be float (can be decimal)
     pub fn scroll_top(&self) -> i32 {
-        self.inner.scroll_top()
+        self.raw().scroll_top()
     }
     pub fn set_scroll_top(&self, scroll_top: i32) {
-        self.inner.set_scroll_top(scroll_top)
+        self.raw().set_scroll_top(scroll_top)
     }
     pub fn scroll_left(&self) -> i32 {
-        self.inner.scroll_left()
+        self.raw().scroll_left()
     }
     pub fn set_scroll_left(&self, scroll_left: i32) {
-        self.inner.set_scroll_left(scroll_left)
+        self.raw().set_scroll_left(scroll_left)
     }
     // getBoundingClientRect is better
     pub fn scroll_width(&self) -> i32 {
-        self.inner.scroll_width()
+        self.raw().scroll_width()
     }
     pub fn scroll_height(&self) -> i32 {
-        self.inner.scroll_height()
+        self.raw().scroll_height()
     }
     pub fn client_top(&self) -> i32 {
-        self.inner.client_top()
+        self.raw().client_top()
     }
     pub fn client_left(&self) -> i32 {
-        self.inner.client_left()
+        self.raw().client_left()
     }
     pub fn client_width(&self) -> i32 {
-        self.inner.client_width()
+        self.raw().client_width()
     }
     pub fn client_height(&self) -> i32 {
-        self.inner.client_height()
+        self.raw().client_height()
     }
     pub fn inner_html(&self) -> String {
-        self.inner.inner_html()
+        self.raw().inner_html()
     }
     pub fn set_inner_html(&self, inner_html: &str) {
-        self.inner.set_inner_html(inner_html)
+        self.raw().set_inner_html(inner_html)
     }
     pub fn outer_html(&self) -> String {
-        self.inner.outer_html()
+        self.raw().outer_html()
     }
     pub fn set_outer_html(&self, outer_html: &str) {
-        self.inner.set_outer_html(outer_html)
+        self.raw().set_outer_html(outer_html)
     }
     // todo skipping shadow dom stuff
     // skipping ChildNode, NonDocumentTypeChildNode, ParentNode::{append, prepend}
     //pub fn children(&self) -> HtmlCollection;
     pub fn first_element_child(&self) -> Option<Element> {
-        self.inner.first_element_child().map(Element::from_web_sys)
+        self.raw().first_element_child().map(Element::from_handle)
     }
     pub fn last_element_child(&self) -> Option<Element> {
-        self.inner.last_element_child().map(Element::from_web_sys)
+        self.raw().last_element_child().map(Element::from_handle)
     }
     pub fn child_element_count(&self) -> u32 {
-        self.inner.child_element_count()
+        self.raw().child_element_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{error::DomExceptionKind, event::EventKind};
+    use std::{cell::RefCell, rc::Rc};
+
+    #[test]
+    fn delegate() {
+        let document = crate::backend::memory::MemoryBackend.new_document();
+        let document = crate::document::Document::from_handle(document);
+        let table = document.create_element("table");
+        table.set_inner_html("<tr data-id=1><td><b>one</b></td></tr><tr><td>two</td></tr>");
+        document.body().append_child(&table);
+
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let seen_copy = seen.clone();
+        let _guard = table
+            .delegate(EventKind::Click, "tr[data-id]", move |_, row| {
+                seen_copy.borrow_mut().push(row.get_attribute("data-id"));
+            })
+            .unwrap();
+        let mut init = crate::event::EventInit::default();
+        init.bubbles(true);
+        for cell in table.query_selector_all("td").unwrap() {
+            cell.dispatch_event(crate::event::Event::new_with_init(EventKind::Click, &init));
+        }
+        table.dispatch_event(crate::event::Event::new_with_init(EventKind::Click, &init));
+        assert_eq!(*seen.borrow(), vec![Some("1".to_owned())]);
+
+        let err = table
+            .delegate(EventKind::Click, "tr[", |_, _| ())
+            .unwrap_err();
+        assert_eq!(*err.kind(), DomExceptionKind::Syntax);
     }
 }
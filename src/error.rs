@@ -0,0 +1,181 @@
+//! Errors thrown by DOM operations.
+
+use std::{error::Error, fmt};
+use wasm_bindgen::{JsCast, JsValue};
+
+macro_rules! exception_kinds {
+    ($($(#[$meta:meta])* $kind:ident => $name:expr,)*) => {
+        /// The kind of a `DomException`, named after the exception names in the WebIDL spec.
+        #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+        pub enum DomExceptionKind {
+            $($(#[$meta])* $kind,)*
+            /// An exception that isn't in the list above, e.g. a javascript `TypeError`.
+            Other(String),
+        }
+
+        impl DomExceptionKind {
+            /// The name of the exception as it appears in javascript, e.g. `NotFoundError`.
+            pub fn name(&self) -> &str {
+                match self {
+                    $(DomExceptionKind::$kind => $name,)*
+                    DomExceptionKind::Other(name) => name,
+                }
+            }
+
+            /// Look up the kind from its javascript name.
+            pub fn from_name(name: &str) -> Self {
+                match name {
+                    $($name => DomExceptionKind::$kind,)*
+                    other => DomExceptionKind::Other(other.to_owned()),
+                }
+            }
+        }
+    };
+}
+
+exception_kinds! {
+    /// An index or size is negative or too large.
+    IndexSize => "IndexSizeError",
+    /// The operation would give an incorrect node tree, for example appending a node to one of
+    /// its own descendants.
+    HierarchyRequest => "HierarchyRequestError",
+    /// The object is in the wrong document.
+    WrongDocument => "WrongDocumentError",
+    /// A string contains characters that are not allowed, e.g. in an attribute name.
+    InvalidCharacter => "InvalidCharacterError",
+    /// The object can not be modified.
+    NoModificationAllowed => "NoModificationAllowedError",
+    /// The object can not be found here.
+    NotFound => "NotFoundError",
+    /// The operation is not supported.
+    NotSupported => "NotSupportedError",
+    /// The attribute is in use by another element.
+    InUseAttribute => "InUseAttributeError",
+    /// The object is in an invalid state.
+    InvalidState => "InvalidStateError",
+    /// The string did not match the expected pattern, e.g. an invalid css selector.
+    Syntax => "SyntaxError",
+    /// The object can not be modified in this way.
+    InvalidModification => "InvalidModificationError",
+    /// The operation is not allowed by namespaces in xml.
+    Namespace => "NamespaceError",
+    /// The object does not support the operation or argument.
+    InvalidAccess => "InvalidAccessError",
+    /// The operation is insecure.
+    Security => "SecurityError",
+    /// A network error occurred.
+    Network => "NetworkError",
+    /// The operation was aborted.
+    Abort => "AbortError",
+    /// The quota has been exceeded.
+    QuotaExceeded => "QuotaExceededError",
+    /// The operation timed out.
+    Timeout => "TimeoutError",
+    /// The supplied node is incorrect or has an incorrect ancestor for this operation.
+    InvalidNodeType => "InvalidNodeTypeError",
+    /// The object can not be cloned.
+    DataClone => "DataCloneError",
+    /// The encoding or decoding operation failed.
+    Encoding => "EncodingError",
+    /// The input/output read operation failed.
+    NotReadable => "NotReadableError",
+    /// The operation failed for an unknown transient reason.
+    Unknown => "UnknownError",
+    /// A mutation operation in a transaction failed because a constraint was not satisfied.
+    Constraint => "ConstraintError",
+    /// Provided data is inadequate.
+    Data => "DataError",
+    /// A request was placed against a transaction that is not active.
+    TransactionInactive => "TransactionInactiveError",
+    /// The mutating operation was attempted in a "readonly" transaction.
+    ReadOnly => "ReadOnlyError",
+    /// An attempt was made to open a database using a lower version than the existing version.
+    Version => "VersionError",
+    /// The operation failed for an operation-specific reason.
+    Operation => "OperationError",
+    /// The request is not allowed by the user agent or the platform in the current context.
+    NotAllowed => "NotAllowedError",
+}
+
+/// An exception thrown by a DOM operation.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DomException {
+    kind: DomExceptionKind,
+    message: String,
+}
+
+impl DomException {
+    pub fn new(kind: DomExceptionKind, message: impl Into<String>) -> Self {
+        DomException {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    pub fn kind(&self) -> &DomExceptionKind {
+        &self.kind
+    }
+
+    /// The name of the exception, for example `HierarchyRequestError`.
+    pub fn name(&self) -> &str {
+        self.kind.name()
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Convert an exception thrown by the browser.
+    pub(crate) fn from_js(err: JsValue) -> Self {
+        if let Some(err) = err.dyn_ref::<web_sys::DomException>() {
+            DomException::new(DomExceptionKind::from_name(&err.name()), err.message())
+        } else if let Some(err) = err.dyn_ref::<js_sys::Error>() {
+            DomException::new(
+                DomExceptionKind::from_name(&String::from(err.name())),
+                String::from(err.message()),
+            )
+        } else {
+            DomException::new(DomExceptionKind::Unknown, format!("{:?}", err))
+        }
+    }
+
+    pub(crate) fn mixed_backends() -> Self {
+        DomException::new(
+            DomExceptionKind::NotSupported,
+            "cannot mix objects from different backends",
+        )
+    }
+}
+
+impl fmt::Display for DomException {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.message.is_empty() {
+            f.write_str(self.name())
+        } else {
+            write!(f, "{}: {}", self.name(), self.message)
+        }
+    }
+}
+
+impl Error for DomException {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names() {
+        let kind = DomExceptionKind::from_name("HierarchyRequestError");
+        assert_eq!(kind, DomExceptionKind::HierarchyRequest);
+        assert_eq!(kind.name(), "HierarchyRequestError");
+        assert_eq!(
+            DomExceptionKind::from_name("TypeError"),
+            DomExceptionKind::Other("TypeError".to_owned())
+        );
+        let err = DomException::new(kind, "the new child is an ancestor of the parent");
+        assert_eq!(
+            err.to_string(),
+            "HierarchyRequestError: the new child is an ancestor of the parent"
+        );
+    }
+}
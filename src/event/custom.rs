@@ -0,0 +1,197 @@
+//! Events carrying a rust value, for components to talk to each other through the DOM.
+
+use std::{any::Any, cell::RefCell, collections::HashMap, fmt, ops::Deref, rc::Rc};
+use wasm_bindgen::JsValue;
+
+use super::{ui, Event, EventData, EventInit, EventKind, FromEvent};
+use crate::error::DomException;
+
+/// An event with a rust value as its detail.
+///
+/// The value is never converted to javascript, so it can be of any type. Listeners get it back
+/// by downcasting to a `CustomEvent` of the same type:
+///
+/// ```rust,no_run
+/// use dommer::event::{CustomEvent, EventInit, EventKind};
+///
+/// struct Selected {
+///     id: u32,
+/// }
+///
+/// let body = dommer::document().body();
+/// let kind = EventKind::Other("selected".into());
+/// let _guard = body.add_event_listener(kind.clone(), |event| {
+///     if let Ok(event) = event.downcast::<CustomEvent<Selected>>() {
+///         dommer::println!("selected {}", event.detail().id);
+///     }
+/// });
+/// let mut init = EventInit::default();
+/// init.bubbles(true);
+/// body.dispatch_event(CustomEvent::new(kind, &init, Selected { id: 3 }));
+/// ```
+pub struct CustomEvent<T> {
+    event: Event,
+    detail: Rc<T>,
+}
+
+impl<T: 'static> CustomEvent<T> {
+    pub fn new(kind: EventKind, init: &EventInit, detail: T) -> Self {
+        expect!(
+            CustomEvent::try_new(kind, init, detail),
+            "creating a `CustomEvent`"
+        )
+    }
+
+    pub fn try_new(kind: EventKind, init: &EventInit, detail: T) -> Result<Self, DomException> {
+        let detail = Rc::new(detail);
+        let event = ui::create(kind, init, &EventData::Custom(detail.clone()))?;
+        Ok(CustomEvent { event, detail })
+    }
+
+    pub fn detail(&self) -> &T {
+        &self.detail
+    }
+}
+
+impl<T> Clone for CustomEvent<T> {
+    fn clone(&self) -> Self {
+        CustomEvent {
+            event: self.event.clone(),
+            detail: self.detail.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for CustomEvent<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CustomEvent")
+            .field("event", &self.event)
+            .field("detail", &self.detail)
+            .finish()
+    }
+}
+
+impl<T> Deref for CustomEvent<T> {
+    type Target = Event;
+
+    fn deref(&self) -> &Event {
+        &self.event
+    }
+}
+
+impl<T> From<CustomEvent<T>> for Event {
+    fn from(event: CustomEvent<T>) -> Event {
+        event.event
+    }
+}
+
+impl<T: 'static> FromEvent for CustomEvent<T> {
+    fn from_event(event: Event) -> Result<Self, Event> {
+        match event.raw.data() {
+            EventData::Custom(detail) => match detail.downcast() {
+                Ok(detail) => Ok(CustomEvent { event, detail }),
+                Err(_) => Err(event),
+            },
+            _ => Err(event),
+        }
+    }
+}
+
+// The browser only sees a key into this registry, so the payload can stay in rust.
+#[derive(Default)]
+struct Registry {
+    next_id: u32,
+    payloads: HashMap<u32, Rc<dyn Any>>,
+}
+
+thread_local! {
+    static REGISTRY: RefCell<Registry> = RefCell::new(Registry::default());
+}
+
+const KEY: &str = "dommerPayload";
+
+/// Keeps a payload where browser events can find it, until dropped.
+#[derive(Debug)]
+pub(crate) struct PayloadGuard(u32);
+
+impl Drop for PayloadGuard {
+    fn drop(&mut self) {
+        REGISTRY.with(|registry| registry.borrow_mut().payloads.remove(&self.0));
+    }
+}
+
+/// Store `payload`, returning the detail to give the browser's `CustomEvent`.
+pub(crate) fn register(payload: Rc<dyn Any>) -> (JsValue, PayloadGuard) {
+    let id = REGISTRY.with(|registry| {
+        let mut registry = registry.borrow_mut();
+        let id = registry.next_id;
+        registry.next_id = id.wrapping_add(1);
+        registry.payloads.insert(id, payload);
+        id
+    });
+    let detail = js_sys::Object::new();
+    // Setting a property on a plain object can't fail.
+    let _ = js_sys::Reflect::set(&detail, &JsValue::from_str(KEY), &JsValue::from(id));
+    (detail.into(), PayloadGuard(id))
+}
+
+/// Find the payload for the detail of a browser `CustomEvent`.
+pub(crate) fn lookup(detail: &JsValue) -> Option<Rc<dyn Any>> {
+    if !detail.is_object() {
+        return None;
+    }
+    let id = js_sys::Reflect::get(detail, &JsValue::from_str(KEY))
+        .ok()?
+        .as_f64()?;
+    REGISTRY.with(|registry| registry.borrow().payloads.get(&(id as u32)).cloned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::EventTarget;
+    use std::cell::Cell;
+
+    #[derive(Debug, PartialEq)]
+    struct Selected {
+        id: u32,
+    }
+
+    #[test]
+    fn custom_event() {
+        let kind = EventKind::Other("selected".into());
+        let target = EventTarget::new();
+        let seen = Rc::new(Cell::new(0));
+        let seen_copy = seen.clone();
+        let _guard = target.add_event_listener(kind.clone(), move |event| {
+            assert!(!event.is::<CustomEvent<String>>());
+            let event = event.downcast::<CustomEvent<Selected>>().unwrap();
+            seen_copy.set(event.detail().id);
+            event.prevent_default();
+        });
+        let mut init = EventInit::default();
+        init.cancelable(true);
+        let event = CustomEvent::new(kind, &init, Selected { id: 7 });
+        assert!(!target.dispatch_event(event.clone()));
+        assert_eq!(seen.get(), 7);
+        assert_eq!(*event.detail(), Selected { id: 7 });
+    }
+
+    #[test]
+    fn detail_outlives_dispatch() {
+        use futures::executor::block_on;
+
+        let kind = EventKind::Other("selected".into());
+        let target = EventTarget::new();
+        let next = target.next_event(kind.clone());
+        // The dispatched event is dropped before anything downcasts the one that was received.
+        // This runs on the memory backend; `WebEvent` keeps its own copy, which needs a browser.
+        target.dispatch_event(CustomEvent::new(
+            kind,
+            &EventInit::default(),
+            Selected { id: 3 },
+        ));
+        let event = block_on(next).downcast::<CustomEvent<Selected>>().unwrap();
+        assert_eq!(*event.detail(), Selected { id: 3 });
+    }
+}
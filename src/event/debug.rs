@@ -0,0 +1,92 @@
+//! Counting live listeners, to find leaks. Only built with the `debug-listeners` feature.
+
+use std::cell::RefCell;
+
+use super::EventTarget;
+use crate::backend::Unsubscribe;
+
+thread_local! {
+    // A list rather than a map, since handles can only be compared with `is_same_target`.
+    static LIVE: RefCell<Vec<(EventTarget, usize)>> = RefCell::new(Vec::new());
+}
+
+/// Count a new listener on `target`, until `unsubscribe` is called.
+pub(super) fn track(target: &EventTarget, mut unsubscribe: Unsubscribe) -> impl FnMut() {
+    added(target);
+    let target = target.clone();
+    move || {
+        unsubscribe();
+        removed(&target);
+    }
+}
+
+fn added(target: &EventTarget) {
+    LIVE.with(|live| {
+        let mut live = live.borrow_mut();
+        match live
+            .iter_mut()
+            .find(|(other, _)| other.raw.is_same_target(&*target.raw))
+        {
+            Some((_, count)) => *count += 1,
+            None => live.push((target.clone(), 1)),
+        }
+    })
+}
+
+fn removed(target: &EventTarget) {
+    // Take the target out before dropping it, in case that runs more code that counts listeners.
+    let _removed = LIVE.with(|live| {
+        let mut live = live.borrow_mut();
+        let idx = live
+            .iter()
+            .position(|(other, _)| other.raw.is_same_target(&*target.raw))?;
+        live[idx].1 -= 1;
+        if live[idx].1 == 0 {
+            Some(live.remove(idx))
+        } else {
+            None
+        }
+    });
+}
+
+/// The number of listeners on `target` whose guards haven't been dropped.
+pub(super) fn count(target: &EventTarget) -> usize {
+    LIVE.with(|live| {
+        live.borrow()
+            .iter()
+            .find(|(other, _)| other.raw.is_same_target(&*target.raw))
+            .map_or(0, |(_, count)| *count)
+    })
+}
+
+/// Every target with listeners whose guards haven't been dropped, along with how many listeners
+/// it has. Forgotten guards are counted as live, since their listeners are never removed.
+///
+/// The targets are kept alive until all their listeners are removed.
+pub fn live_listeners() -> Vec<(EventTarget, usize)> {
+    LIVE.with(|live| live.borrow().clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::event::{EventKind, EventTarget};
+
+    #[test]
+    fn live_listeners() {
+        let target = EventTarget::new();
+        let other = EventTarget::new();
+        let first = target.add_event_listener(EventKind::Click, |_| ());
+        let second = target.add_event_listener(EventKind::KeyDown, |_| ());
+        other.add_event_listener(EventKind::Click, |_| ()).forget();
+        assert_eq!(target.live_listeners(), 2);
+        assert_eq!(other.live_listeners(), 1);
+        drop(first);
+        assert_eq!(target.live_listeners(), 1);
+        drop(second);
+        assert_eq!(target.live_listeners(), 0);
+        let live = super::live_listeners();
+        assert!(live
+            .iter()
+            .all(|(live, _)| !live.raw.is_same_target(&*target.raw)));
+    }
+}
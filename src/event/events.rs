@@ -0,0 +1,172 @@
+//! Zero-sized markers for each kind of event, that know which type of event they carry.
+//!
+//! Use them with `EventTarget::add_listener` to get the right type of event in the listener,
+//! without downcasting by hand:
+//!
+//! ```rust,no_run
+//! use dommer::event::{events::Click, MouseEvent};
+//!
+//! let body = dommer::document().body();
+//! let _guard = body.add_listener::<Click>(|event: MouseEvent| {
+//!     dommer::println!("clicked at ({}, {})", event.client_x(), event.client_y());
+//! });
+//! ```
+//!
+//! Kinds whose events have no wrapper of their own, like touch and clipboard events, give the plain
+//! `Event`. Drag events are mouse events. For kinds without a marker, use `EventKind::Other` with
+//! `EventTarget::add_event_listener`.
+
+use super::{
+    CompositionEvent, Event, EventKind, FocusEvent, FromEvent, InputEvent, KeyboardEvent,
+    MouseEvent, PointerEvent, WheelEvent,
+};
+
+/// A kind of event, along with the type of event listeners for it receive.
+pub trait StaticEvent {
+    /// The type of event fired for this kind.
+    type Event: FromEvent;
+
+    fn kind() -> EventKind;
+}
+
+macro_rules! events {
+    ($($(#[$meta:meta])* $name:ident => $event:ty;)*) => {
+        $(
+            $(#[$meta])*
+            #[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+            pub struct $name;
+
+            impl StaticEvent for $name {
+                type Event = $event;
+
+                fn kind() -> EventKind {
+                    EventKind::$name
+                }
+            }
+        )*
+    };
+}
+
+events! {
+    Abort => Event;
+    AfterPrint => Event;
+    AnimationCancel => Event;
+    AnimationEnd => Event;
+    AnimationIteration => Event;
+    AnimationStart => Event;
+    AuxClick => MouseEvent;
+    BeforeInput => InputEvent;
+    BeforePrint => Event;
+    BeforeToggle => Event;
+    BeforeUnload => Event;
+    Blur => FocusEvent;
+    Cancel => Event;
+    CanPlay => Event;
+    CanPlayThrough => Event;
+    Change => Event;
+    Click => MouseEvent;
+    Close => Event;
+    CompositionEnd => CompositionEvent;
+    CompositionStart => CompositionEvent;
+    CompositionUpdate => CompositionEvent;
+    ContextMenu => MouseEvent;
+    Copy => Event;
+    Cut => Event;
+    DomContentLoaded => Event;
+    DoubleClick => MouseEvent;
+    Drag => MouseEvent;
+    DragEnd => MouseEvent;
+    DragEnter => MouseEvent;
+    DragLeave => MouseEvent;
+    DragOver => MouseEvent;
+    DragStart => MouseEvent;
+    Drop => MouseEvent;
+    DurationChange => Event;
+    Emptied => Event;
+    Ended => Event;
+    Error => Event;
+    Focus => FocusEvent;
+    FocusIn => FocusEvent;
+    FocusOut => FocusEvent;
+    FormData => Event;
+    FullscreenChange => Event;
+    FullscreenError => Event;
+    GotPointerCapture => PointerEvent;
+    HashChange => Event;
+    /// Only an `InputEvent` for editable elements, so this gives the plain `Event`.
+    Input => Event;
+    Invalid => Event;
+    KeyDown => KeyboardEvent;
+    KeyPress => KeyboardEvent;
+    KeyUp => KeyboardEvent;
+    LanguageChange => Event;
+    Load => Event;
+    LoadedData => Event;
+    LoadedMetadata => Event;
+    LoadEnd => Event;
+    LoadStart => Event;
+    LostPointerCapture => PointerEvent;
+    Message => Event;
+    MessageError => Event;
+    MouseDown => MouseEvent;
+    MouseEnter => MouseEvent;
+    MouseLeave => MouseEvent;
+    MouseMove => MouseEvent;
+    MouseOut => MouseEvent;
+    MouseOver => MouseEvent;
+    MouseUp => MouseEvent;
+    Offline => Event;
+    Online => Event;
+    PageHide => Event;
+    PageShow => Event;
+    Paste => Event;
+    Pause => Event;
+    Play => Event;
+    Playing => Event;
+    PointerCancel => PointerEvent;
+    PointerDown => PointerEvent;
+    PointerEnter => PointerEvent;
+    PointerLeave => PointerEvent;
+    PointerMove => PointerEvent;
+    PointerOut => PointerEvent;
+    PointerOver => PointerEvent;
+    PointerRawUpdate => PointerEvent;
+    PointerUp => PointerEvent;
+    PopState => Event;
+    Progress => Event;
+    RateChange => Event;
+    ReadyStateChange => Event;
+    RejectionHandled => Event;
+    Reset => Event;
+    Resize => Event;
+    Scroll => Event;
+    ScrollEnd => Event;
+    SecurityPolicyViolation => Event;
+    Seeked => Event;
+    Seeking => Event;
+    Select => Event;
+    SelectionChange => Event;
+    SelectStart => Event;
+    SlotChange => Event;
+    Stalled => Event;
+    Storage => Event;
+    Submit => Event;
+    Suspend => Event;
+    TimeUpdate => Event;
+    Timeout => Event;
+    Toggle => Event;
+    TouchCancel => Event;
+    TouchEnd => Event;
+    TouchMove => Event;
+    TouchStart => Event;
+    TransitionCancel => Event;
+    TransitionEnd => Event;
+    TransitionRun => Event;
+    TransitionStart => Event;
+    UnhandledRejection => Event;
+    Unload => Event;
+    VisibilityChange => Event;
+    VolumeChange => Event;
+    Waiting => Event;
+    Wheel => WheelEvent;
+}
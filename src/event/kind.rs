@@ -0,0 +1,208 @@
+//! The names of events.
+
+use std::{borrow::Cow, convert::Infallible, str::FromStr};
+
+macro_rules! event_kinds {
+    ($($(#[$meta:meta])* $name:ident => $str:literal,)*) => {
+        /// The kind of an event, its `type` in javascript.
+        ///
+        /// Kinds without a variant of their own can be named with `Other`. Note that `Other` should
+        /// not be used for the kinds that do have a variant, since `Other("click".into())` is not
+        /// equal to `Click`. Converting from a string always gives the right variant.
+        #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+        pub enum EventKind {
+            $($(#[$meta])* $name,)*
+            Other(Cow<'static, str>),
+        }
+
+        impl EventKind {
+            /// Every kind, except `Other`.
+            pub const ALL: &'static [EventKind] = &[$(EventKind::$name),*];
+
+            /// The name javascript uses for this kind.
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(EventKind::$name => $str,)*
+                    EventKind::Other(other) => other,
+                }
+            }
+
+            fn from_known(name: &str) -> Option<EventKind> {
+                match name {
+                    $($str => Some(EventKind::$name),)*
+                    _ => None,
+                }
+            }
+        }
+
+        impl From<EventKind> for Cow<'static, str> {
+            fn from(kind: EventKind) -> Cow<'static, str> {
+                match kind {
+                    $(EventKind::$name => Cow::Borrowed($str),)*
+                    EventKind::Other(other) => other,
+                }
+            }
+        }
+    };
+}
+
+event_kinds! {
+    Abort => "abort",
+    AfterPrint => "afterprint",
+    AnimationCancel => "animationcancel",
+    AnimationEnd => "animationend",
+    AnimationIteration => "animationiteration",
+    AnimationStart => "animationstart",
+    AuxClick => "auxclick",
+    BeforeInput => "beforeinput",
+    BeforePrint => "beforeprint",
+    BeforeToggle => "beforetoggle",
+    BeforeUnload => "beforeunload",
+    Blur => "blur",
+    Cancel => "cancel",
+    CanPlay => "canplay",
+    CanPlayThrough => "canplaythrough",
+    Change => "change",
+    Click => "click",
+    Close => "close",
+    CompositionEnd => "compositionend",
+    CompositionStart => "compositionstart",
+    CompositionUpdate => "compositionupdate",
+    ContextMenu => "contextmenu",
+    Copy => "copy",
+    Cut => "cut",
+    DomContentLoaded => "DOMContentLoaded",
+    DoubleClick => "dblclick",
+    Drag => "drag",
+    DragEnd => "dragend",
+    DragEnter => "dragenter",
+    DragLeave => "dragleave",
+    DragOver => "dragover",
+    DragStart => "dragstart",
+    Drop => "drop",
+    DurationChange => "durationchange",
+    Emptied => "emptied",
+    Ended => "ended",
+    Error => "error",
+    Focus => "focus",
+    FocusIn => "focusin",
+    FocusOut => "focusout",
+    FormData => "formdata",
+    FullscreenChange => "fullscreenchange",
+    FullscreenError => "fullscreenerror",
+    GotPointerCapture => "gotpointercapture",
+    HashChange => "hashchange",
+    Input => "input",
+    Invalid => "invalid",
+    KeyDown => "keydown",
+    KeyPress => "keypress",
+    KeyUp => "keyup",
+    LanguageChange => "languagechange",
+    Load => "load",
+    LoadedData => "loadeddata",
+    LoadedMetadata => "loadedmetadata",
+    LoadEnd => "loadend",
+    LoadStart => "loadstart",
+    LostPointerCapture => "lostpointercapture",
+    Message => "message",
+    MessageError => "messageerror",
+    MouseDown => "mousedown",
+    MouseEnter => "mouseenter",
+    MouseLeave => "mouseleave",
+    MouseMove => "mousemove",
+    MouseOut => "mouseout",
+    MouseOver => "mouseover",
+    MouseUp => "mouseup",
+    Offline => "offline",
+    Online => "online",
+    PageHide => "pagehide",
+    PageShow => "pageshow",
+    Paste => "paste",
+    Pause => "pause",
+    Play => "play",
+    Playing => "playing",
+    PointerCancel => "pointercancel",
+    PointerDown => "pointerdown",
+    PointerEnter => "pointerenter",
+    PointerLeave => "pointerleave",
+    PointerMove => "pointermove",
+    PointerOut => "pointerout",
+    PointerOver => "pointerover",
+    PointerRawUpdate => "pointerrawupdate",
+    PointerUp => "pointerup",
+    PopState => "popstate",
+    Progress => "progress",
+    RateChange => "ratechange",
+    ReadyStateChange => "readystatechange",
+    RejectionHandled => "rejectionhandled",
+    Reset => "reset",
+    Resize => "resize",
+    Scroll => "scroll",
+    ScrollEnd => "scrollend",
+    SecurityPolicyViolation => "securitypolicyviolation",
+    Seeked => "seeked",
+    Seeking => "seeking",
+    Select => "select",
+    SelectionChange => "selectionchange",
+    SelectStart => "selectstart",
+    SlotChange => "slotchange",
+    Stalled => "stalled",
+    Storage => "storage",
+    Submit => "submit",
+    Suspend => "suspend",
+    TimeUpdate => "timeupdate",
+    Timeout => "timeout",
+    Toggle => "toggle",
+    TouchCancel => "touchcancel",
+    TouchEnd => "touchend",
+    TouchMove => "touchmove",
+    TouchStart => "touchstart",
+    TransitionCancel => "transitioncancel",
+    TransitionEnd => "transitionend",
+    TransitionRun => "transitionrun",
+    TransitionStart => "transitionstart",
+    UnhandledRejection => "unhandledrejection",
+    Unload => "unload",
+    VisibilityChange => "visibilitychange",
+    VolumeChange => "volumechange",
+    Waiting => "waiting",
+    Wheel => "wheel",
+}
+
+impl From<String> for EventKind {
+    fn from(kind: String) -> EventKind {
+        match EventKind::from_known(&kind) {
+            Some(known) => known,
+            None => EventKind::Other(Cow::Owned(kind)),
+        }
+    }
+}
+
+/// Only allocates for kinds without a variant of their own, which become `Other`.
+impl FromStr for EventKind {
+    type Err = Infallible;
+
+    fn from_str(kind: &str) -> Result<EventKind, Infallible> {
+        Ok(match EventKind::from_known(kind) {
+            Some(known) => known,
+            None => EventKind::Other(Cow::Owned(kind.to_owned())),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let other = EventKind::Other("my-event".into());
+        for kind in EventKind::ALL.iter().chain(Some(&other)) {
+            let name = Cow::from(kind.clone());
+            assert_eq!(name, kind.as_str());
+            assert_eq!(EventKind::from(name.clone().into_owned()), *kind);
+            assert_eq!(name.parse::<EventKind>().unwrap(), *kind);
+        }
+        assert_eq!(EventKind::from("click".to_owned()), EventKind::Click);
+    }
+}
@@ -0,0 +1,804 @@
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::{Rc, Weak};
+
+use crate::{
+    abort::AbortSignal,
+    backend::{self, web::WebEvent, EventHandle, Handle, Listener, Unsubscribe},
+    error::DomException,
+};
+
+pub(crate) mod custom;
+#[cfg(feature = "debug-listeners")]
+mod debug;
+pub mod events;
+mod kind;
+mod scope;
+mod stream;
+mod ui;
+
+pub use custom::CustomEvent;
+#[cfg(feature = "debug-listeners")]
+pub use debug::live_listeners;
+pub use kind::EventKind;
+pub use scope::Scope;
+pub use stream::{EventStream, NextEvent};
+
+pub(crate) use ui::{data_from_web_sys, new_web_sys};
+
+use events::StaticEvent;
+pub use ui::{
+    CompositionEvent, CompositionEventData, DeltaMode, EventData, FocusEvent, FocusEventData,
+    FromEvent, InputEvent, InputEventData, KeyLocation, KeyboardEvent, KeyboardEventData,
+    Modifiers, MouseButton, MouseButtons, MouseEvent, MouseEventData, PointerEvent,
+    PointerEventData, PointerType, WheelEvent, WheelEventData,
+};
+
+/// A guard on a callback that will unregister the callback when it goes out of scope. Makes things
+/// like `window.addEventListener` RAII.
+pub struct SubscribeGuard {
+    // This could actually be FnOnce, since we will call it only once.
+    unsubscribe: Option<Box<dyn FnMut()>>,
+    listener: Option<(EventKind, AddEventListenerOptions)>,
+}
+
+impl SubscribeGuard {
+    pub(crate) fn new(unsubscribe: impl FnMut() + 'static) -> Self {
+        SubscribeGuard {
+            unsubscribe: Some(Box::new(unsubscribe)),
+            listener: None,
+        }
+    }
+
+    /// The kind of event the listener is for, if this guards an event listener.
+    pub fn kind(&self) -> Option<&EventKind> {
+        self.listener.as_ref().map(|(kind, _)| kind)
+    }
+
+    /// The options the listener was added with, if this guards an event listener.
+    pub fn options(&self) -> Option<&AddEventListenerOptions> {
+        self.listener.as_ref().map(|(_, options)| options)
+    }
+
+    /// Drop the guard without removing the listener, which then lives as long as its target.
+    pub fn forget(mut self) {
+        // The unsubscribe function can own the listener, so it has to be leaked too.
+        std::mem::forget(self.unsubscribe.take());
+    }
+}
+
+impl fmt::Debug for SubscribeGuard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SubscribeGuard")
+            .field("kind", &self.kind())
+            .finish()
+    }
+}
+
+impl Drop for SubscribeGuard {
+    fn drop(&mut self) {
+        if let Some(mut unsubscribe) = self.unsubscribe.take() {
+            unsubscribe()
+        }
+    }
+}
+
+/// A listener that can be removed by its guard, by itself after firing if it is `once`, or by its
+/// abort signal, whichever comes first.
+#[derive(Default)]
+struct Registration {
+    unsubscribe: Option<Unsubscribe>,
+    on_abort: Option<SubscribeGuard>,
+}
+
+impl Registration {
+    fn remove(this: &RefCell<Registration>) {
+        // Take everything out first, since removing the listener can drop the closure that is
+        // calling this.
+        let (unsubscribe, on_abort) = {
+            let mut this = this.borrow_mut();
+            (this.unsubscribe.take(), this.on_abort.take())
+        };
+        if let Some(mut unsubscribe) = unsubscribe {
+            unsubscribe();
+        }
+        drop(on_abort);
+    }
+}
+
+/// Lets a listener added with `EventTarget::add_event_listener_with_handle` remove itself.
+pub struct ListenerHandle {
+    guard: Weak<RefCell<Option<SubscribeGuard>>>,
+}
+
+impl ListenerHandle {
+    /// Remove the listener, as if its guard had been dropped. The listener finishes its current
+    /// call, but isn't called again.
+    pub fn unsubscribe(&self) {
+        if let Some(guard) = self.guard.upgrade() {
+            let guard = guard.borrow_mut().take();
+            drop(guard);
+        }
+    }
+}
+
+impl fmt::Debug for ListenerHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("ListenerHandle")
+    }
+}
+
+/// An event, either received by a listener or created to be dispatched.
+#[derive(Debug, Clone)]
+pub struct Event {
+    pub(crate) raw: Rc<dyn EventHandle>,
+}
+
+impl Event {
+    pub fn new(kind: EventKind) -> Event {
+        Event::new_with_init(kind, &Default::default())
+    }
+
+    pub fn new_with_init(kind: EventKind, init: &EventInit) -> Event {
+        expect!(Event::try_new_with_init(kind, init), "creating an `Event`")
+    }
+
+    pub fn try_new_with_init(kind: EventKind, init: &EventInit) -> Result<Event, DomException> {
+        let raw = backend::current().create_event(&Cow::from(kind), init, &EventData::Plain)?;
+        Ok(Event { raw })
+    }
+
+    /// Wrap a handle to an event from any backend.
+    pub fn from_handle(raw: Rc<dyn EventHandle>) -> Event {
+        Event { raw }
+    }
+
+    pub fn kind(&self) -> EventKind {
+        EventKind::from(self.raw.kind())
+    }
+
+    pub fn bubbles(&self) -> bool {
+        self.raw.bubbles()
+    }
+
+    pub fn cancelable(&self) -> bool {
+        self.raw.cancelable()
+    }
+
+    /// Whether the event will cross from a shadow tree into the tree around it.
+    pub fn composed(&self) -> bool {
+        self.raw.composed()
+    }
+
+    /// Whether the event was fired by the browser, rather than dispatched by a script.
+    pub fn is_trusted(&self) -> bool {
+        self.raw.is_trusted()
+    }
+
+    /// When the event was created, in milliseconds.
+    pub fn time_stamp(&self) -> f64 {
+        self.raw.time_stamp()
+    }
+
+    pub fn event_phase(&self) -> EventPhase {
+        self.raw.event_phase()
+    }
+
+    /// Cancel the event's default action, e.g. following a link. Does nothing if the event isn't
+    /// cancelable, or from a passive listener.
+    pub fn prevent_default(&self) {
+        self.raw.prevent_default()
+    }
+
+    pub fn default_prevented(&self) -> bool {
+        self.raw.default_prevented()
+    }
+
+    /// Stop the event going on to any other targets, once the listeners on the current target
+    /// have run.
+    pub fn stop_propagation(&self) {
+        self.raw.stop_propagation()
+    }
+
+    /// Stop the event going on to any other listeners, including those on the current target.
+    pub fn stop_immediate_propagation(&self) {
+        self.raw.stop_immediate_propagation()
+    }
+
+    /// The target the event was dispatched to. Use `Node::try_from` or `Element::try_from` to
+    /// get at the node.
+    pub fn target(&self) -> Option<EventTarget> {
+        self.raw.target().map(EventTarget::from_handle)
+    }
+
+    /// The target whose listeners are being run, or `None` outside of dispatch.
+    pub fn current_target(&self) -> Option<EventTarget> {
+        self.raw.current_target().map(EventTarget::from_handle)
+    }
+
+    /// The targets the event passes through, starting with the target and ending with the root.
+    /// Empty outside of dispatch.
+    pub fn composed_path(&self) -> Vec<EventTarget> {
+        self.raw
+            .composed_path()
+            .into_iter()
+            .map(EventTarget::from_handle)
+            .collect()
+    }
+
+    /// Convert into a more specific type of event, e.g. a `MouseEvent`, or get the event back if
+    /// it is a different type.
+    pub fn downcast<T: FromEvent>(self) -> Result<T, Event> {
+        T::from_event(self)
+    }
+
+    /// Whether `downcast` would succeed.
+    pub fn is<T: FromEvent>(&self) -> bool {
+        T::from_event(self.clone()).is_ok()
+    }
+
+    /// The underlying `web_sys` event, if this event comes from the browser.
+    pub fn as_web_sys(&self) -> Option<&web_sys::Event> {
+        self.raw
+            .as_any()
+            .downcast_ref::<WebEvent>()
+            .map(|event| &event.inner)
+    }
+}
+
+impl From<EventKind> for Event {
+    fn from(kind: EventKind) -> Event {
+        Event::new(kind)
+    }
+}
+
+impl From<web_sys::Event> for Event {
+    fn from(inner: web_sys::Event) -> Self {
+        Event {
+            raw: Rc::new(WebEvent::new(inner)),
+        }
+    }
+}
+
+/// Which part of its path an event is going through.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum EventPhase {
+    /// The event isn't being dispatched.
+    None,
+    /// Going down from the root to the target.
+    Capturing,
+    AtTarget,
+    /// Going back up from the target to the root.
+    Bubbling,
+}
+
+impl EventPhase {
+    pub(crate) fn from_web_sys(phase: u16) -> EventPhase {
+        match phase {
+            web_sys::Event::CAPTURING_PHASE => EventPhase::Capturing,
+            web_sys::Event::AT_TARGET => EventPhase::AtTarget,
+            web_sys::Event::BUBBLING_PHASE => EventPhase::Bubbling,
+            _ => EventPhase::None,
+        }
+    }
+}
+
+dict! {
+    /// Options for creating an `Event`.
+    pub struct EventInit {
+        pub bubbles: bool,
+        pub cancelable: bool,
+        pub composed: bool,
+    }
+}
+
+/// Options for the `add_event_listener` function.
+#[derive(Debug, Clone, Default)]
+pub struct AddEventListenerOptions {
+    pub capture: Option<bool>,
+    /// Remove the listener after it first fires.
+    pub once: Option<bool>,
+    /// Promise not to call `Event::prevent_default`, so the browser can start scrolling straight
+    /// away.
+    pub passive: Option<bool>,
+    /// Remove the listener when the signal is aborted. One signal can be used for many listeners.
+    pub signal: Option<AbortSignal>,
+}
+
+impl AddEventListenerOptions {
+    pub fn capture(&mut self, val: bool) -> &mut Self {
+        self.capture = Some(val);
+        self
+    }
+
+    pub fn once(&mut self, val: bool) -> &mut Self {
+        self.once = Some(val);
+        self
+    }
+
+    pub fn passive(&mut self, val: bool) -> &mut Self {
+        self.passive = Some(val);
+        self
+    }
+
+    pub fn signal(&mut self, val: AbortSignal) -> &mut Self {
+        self.signal = Some(val);
+        self
+    }
+
+    /// Convert into the corresponding web_sys type. The signal is left out, since it is handled
+    /// in rust for every backend.
+    pub(crate) fn into_web_sys(&self) -> Option<web_sys::AddEventListenerOptions> {
+        if self.capture.is_none() && self.once.is_none() && self.passive.is_none() {
+            return None;
+        }
+        let mut opts = web_sys::AddEventListenerOptions::new();
+        if let Some(capture) = self.capture {
+            opts.capture(capture);
+        }
+        if let Some(once) = self.once {
+            opts.once(once);
+        }
+        if let Some(passive) = self.passive {
+            opts.passive(passive);
+        }
+        Some(opts)
+    }
+
+    /// Convert into the corresponding web_sys type.
+    pub(crate) fn into_web_sys_remove(&self) -> Option<web_sys::EventListenerOptions> {
+        match *self {
+            AddEventListenerOptions {
+                capture: Some(capture),
+                ..
+            } => {
+                let mut opts = web_sys::EventListenerOptions::new();
+                opts.capture(capture);
+                Some(opts)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EventTarget {
+    pub(crate) raw: Rc<dyn Handle>,
+}
+
+impl EventTarget {
+    pub fn new() -> Self {
+        expect!(EventTarget::try_new(), "creating an `EventTarget`")
+    }
+
+    /// Fails on browsers too old to support constructing an `EventTarget`.
+    pub fn try_new() -> Result<Self, DomException> {
+        let raw = backend::current().create_event_target()?;
+        Ok(Self { raw })
+    }
+
+    /// Wrap a handle to an event target from any backend.
+    pub fn from_handle(raw: Rc<dyn Handle>) -> Self {
+        Self { raw }
+    }
+
+    /// The backend handle this event target wraps.
+    pub fn handle(&self) -> &Rc<dyn Handle> {
+        &self.raw
+    }
+
+    /// Run the function `listener` when the event type fires on this object.
+    ///
+    /// Unlike the javascript equivalent, this function returns a guard that unregisters the
+    /// callback when it is dropped.
+    pub fn add_event_listener(
+        &self,
+        event_kind: EventKind,
+        listener: impl Fn(Event) + 'static,
+    ) -> SubscribeGuard {
+        self.add_event_listener_opts(event_kind, listener, Default::default())
+    }
+
+    pub fn add_event_listener_opts(
+        &self,
+        event_kind: EventKind,
+        listener: impl Fn(Event) + 'static,
+        options: AddEventListenerOptions,
+    ) -> SubscribeGuard {
+        expect!(
+            self.try_add_event_listener_opts(event_kind, listener, options),
+            "adding an event listener to an `EventTarget`"
+        )
+    }
+
+    pub fn try_add_event_listener_opts(
+        &self,
+        event_kind: EventKind,
+        listener: impl Fn(Event) + 'static,
+        options: AddEventListenerOptions,
+    ) -> Result<SubscribeGuard, DomException> {
+        self.add_listener_rc(event_kind, Rc::new(listener), options)
+    }
+
+    // Not generic, so the abort listener added here doesn't instantiate it again.
+    fn add_listener_rc(
+        &self,
+        event_kind: EventKind,
+        listener: Listener,
+        options: AddEventListenerOptions,
+    ) -> Result<SubscribeGuard, DomException> {
+        if options.signal.as_ref().map(AbortSignal::aborted) == Some(true) {
+            // Like the browser, don't add the listener at all.
+            return Ok(SubscribeGuard {
+                unsubscribe: None,
+                listener: Some((event_kind, options)),
+            });
+        }
+        let registration = Rc::new(RefCell::new(Registration::default()));
+        let listener: Listener = if options.once == Some(true) {
+            // Remove the listener ourselves, so the guard lets go of it straight away.
+            let registration = Rc::downgrade(&registration);
+            Rc::new(move |event| {
+                listener(event);
+                if let Some(registration) = registration.upgrade() {
+                    Registration::remove(&registration);
+                }
+            })
+        } else {
+            listener
+        };
+        let unsubscribe =
+            self.raw
+                .add_event_listener(&Cow::from(event_kind.clone()), listener, &options)?;
+        #[cfg(feature = "debug-listeners")]
+        let unsubscribe: Unsubscribe = Box::new(debug::track(self, unsubscribe));
+        registration.borrow_mut().unsubscribe = Some(unsubscribe);
+        if let Some(signal) = &options.signal {
+            let weak = Rc::downgrade(&registration);
+            let on_abort = signal.add_listener_rc(
+                EventKind::Abort,
+                Rc::new(move |_| {
+                    if let Some(registration) = weak.upgrade() {
+                        Registration::remove(&registration);
+                    }
+                }),
+                Default::default(),
+            )?;
+            registration.borrow_mut().on_abort = Some(on_abort);
+        }
+        Ok(SubscribeGuard {
+            unsubscribe: Some(Box::new(move || Registration::remove(&registration))),
+            listener: Some((event_kind, options)),
+        })
+    }
+
+    /// The number of listeners on this object whose guards haven't been dropped, including
+    /// forgotten guards.
+    #[cfg(feature = "debug-listeners")]
+    pub fn live_listeners(&self) -> usize {
+        debug::count(self)
+    }
+
+    /// Like `add_event_listener`, but `listener` can mutate its state without a `RefCell`.
+    ///
+    /// Panics if the listener is called while it is already running, which happens when it
+    /// dispatches an event that it listens for.
+    pub fn add_event_listener_mut(
+        &self,
+        event_kind: EventKind,
+        listener: impl FnMut(Event) + 'static,
+    ) -> SubscribeGuard {
+        let listener = RefCell::new(listener);
+        let kind = event_kind.clone();
+        self.add_event_listener(event_kind, move |event| match listener.try_borrow_mut() {
+            Ok(mut listener) => listener(event),
+            Err(_) => panic!(
+                "the `FnMut` listener for {:?} events was called while it was already running, \
+                 because it dispatched an event it listens for - use `add_event_listener` with a \
+                 `Fn` listener instead",
+                kind
+            ),
+        })
+    }
+
+    /// Like `add_event_listener_mut`, but `listener` is also passed a handle it can use to remove
+    /// itself, e.g. after it has seen the event it was waiting for.
+    pub fn add_event_listener_with_handle(
+        &self,
+        event_kind: EventKind,
+        mut listener: impl FnMut(Event, &ListenerHandle) + 'static,
+    ) -> SubscribeGuard {
+        let guard = Rc::new(RefCell::new(None));
+        let handle = ListenerHandle {
+            guard: Rc::downgrade(&guard),
+        };
+        let inner = self.add_event_listener_mut(event_kind, move |event| listener(event, &handle));
+        let listener = inner.listener.clone();
+        *guard.borrow_mut() = Some(inner);
+        SubscribeGuard {
+            unsubscribe: Some(Box::new(move || {
+                let inner = guard.borrow_mut().take();
+                drop(inner);
+            })),
+            listener,
+        }
+    }
+
+    /// The events of `event_kind` fired on this object, as a stream. The listener is removed when
+    /// the stream is dropped.
+    pub fn events(&self, event_kind: EventKind) -> EventStream {
+        EventStream::new(|listener| self.add_event_listener(event_kind, listener))
+    }
+
+    /// Wait for the next event of `event_kind` fired on this object, using a `once` listener.
+    pub fn next_event(&self, event_kind: EventKind) -> NextEvent {
+        let mut options = AddEventListenerOptions::default();
+        options.once(true);
+        NextEvent::new(|listener| self.add_event_listener_opts(event_kind, listener, options))
+    }
+
+    /// Run `listener` when the event `E` fires on this object, passing it the type of event `E`
+    /// carries, e.g. `add_listener::<events::Click>(|event: MouseEvent| ..)`.
+    ///
+    /// Events of the right kind but the wrong type, like a plain `Event` dispatched as a `click`,
+    /// are ignored.
+    pub fn add_listener<E: StaticEvent>(
+        &self,
+        listener: impl Fn(E::Event) + 'static,
+    ) -> SubscribeGuard {
+        self.add_listener_opts::<E>(listener, Default::default())
+    }
+
+    pub fn add_listener_opts<E: StaticEvent>(
+        &self,
+        listener: impl Fn(E::Event) + 'static,
+        options: AddEventListenerOptions,
+    ) -> SubscribeGuard {
+        self.add_event_listener_opts(
+            E::kind(),
+            move |event| {
+                if let Ok(event) = event.downcast() {
+                    listener(event)
+                }
+            },
+            options,
+        )
+    }
+
+    /// Returns `false` if a listener cancelled the event. Panics if the event is already being
+    /// dispatched, see `try_dispatch_event`.
+    pub fn dispatch_event(&self, event: impl Into<Event>) -> bool {
+        let event = event.into();
+        expect!(
+            self.try_dispatch_event(event.clone()),
+            "dispatching event {:?}",
+            event
+        )
+    }
+
+    /// Fails with an `InvalidStateError` if the event is already being dispatched.
+    pub fn try_dispatch_event(&self, event: impl Into<Event>) -> Result<bool, DomException> {
+        self.raw.dispatch_event(&event.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn event_target() {
+        let counter = Rc::new(RefCell::new(0u32));
+        let target = EventTarget::new();
+        let counter_copy = counter.clone();
+        let _guard = target.add_event_listener(EventKind::Click, move |_| {
+            *counter_copy.borrow_mut() += 1;
+        });
+        target.dispatch_event(EventKind::Click);
+        target.dispatch_event(EventKind::Click);
+        assert_eq!(*counter.borrow(), 2);
+    }
+
+    #[test]
+    fn downcast() {
+        let seen = Rc::new(RefCell::new(None));
+        let target = EventTarget::new();
+        let seen_copy = seen.clone();
+        let _guard = target.add_event_listener(EventKind::Click, move |event| {
+            assert!(!event.is::<KeyboardEvent>());
+            let event = event.downcast::<MouseEvent>().unwrap();
+            *seen_copy.borrow_mut() = Some((event.client_x(), event.buttons(), event.modifiers()));
+        });
+        let data = MouseEventData {
+            client_x: 12,
+            buttons: MouseButtons::PRIMARY | MouseButtons::SECONDARY,
+            modifiers: Modifiers::SHIFT,
+            ..Default::default()
+        };
+        target.dispatch_event(MouseEvent::new(
+            EventKind::Click,
+            &EventInit::default(),
+            data,
+        ));
+        assert_eq!(
+            *seen.borrow(),
+            Some((
+                12,
+                MouseButtons::PRIMARY | MouseButtons::SECONDARY,
+                Modifiers::SHIFT
+            ))
+        );
+
+        // Pointer events are mouse events too, but plain events are neither.
+        let event: Event = PointerEvent::new(
+            EventKind::Click,
+            &EventInit::default(),
+            Default::default(),
+            Default::default(),
+        )
+        .into();
+        assert!(event.is::<MouseEvent>());
+        assert!(!event.is::<WheelEvent>());
+        assert!(Event::new(EventKind::Click)
+            .downcast::<MouseEvent>()
+            .is_err());
+    }
+
+    #[test]
+    fn add_listener() {
+        let clicks = Rc::new(RefCell::new(Vec::new()));
+        let target = EventTarget::new();
+        let clicks_copy = clicks.clone();
+        let _guard = target.add_listener::<events::Click>(move |event: MouseEvent| {
+            clicks_copy.borrow_mut().push(event.client_x());
+        });
+        let data = MouseEventData {
+            client_x: 3,
+            ..Default::default()
+        };
+        target.dispatch_event(MouseEvent::new(
+            EventKind::Click,
+            &EventInit::default(),
+            data,
+        ));
+        // A plain event can't be passed to the listener.
+        target.dispatch_event(EventKind::Click);
+        assert_eq!(*clicks.borrow(), vec![3]);
+    }
+
+    #[test]
+    fn propagation() {
+        use crate::element::Element;
+        use std::convert::TryFrom;
+
+        let document = crate::backend::memory::MemoryBackend.new_document();
+        let document = crate::document::Document::from_handle(document);
+        let body = document.try_body().unwrap();
+        let button = document.create_element("button");
+        body.append_child(&button);
+
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let seen_copy = seen.clone();
+        let _on_body = body.add_event_listener(EventKind::Click, move |event| {
+            let target = Element::try_from(event.target().unwrap()).unwrap();
+            let current = Element::try_from(event.current_target().unwrap()).unwrap();
+            assert_eq!(event.composed_path().len(), 4);
+            seen_copy.borrow_mut().push((
+                target.tag_name(),
+                current.tag_name(),
+                event.event_phase(),
+            ));
+            event.prevent_default();
+        });
+        let seen_copy = seen.clone();
+        let _on_button = button.add_event_listener(EventKind::Click, move |event| {
+            seen_copy
+                .borrow_mut()
+                .push(("".into(), "".into(), event.event_phase()));
+            event.stop_immediate_propagation();
+        });
+        let _never = button.add_event_listener(EventKind::Click, |_| panic!("stopped"));
+
+        let mut init = EventInit::default();
+        init.bubbles(true).cancelable(true);
+        let event = Event::new_with_init(EventKind::Click, &init);
+        // The button's listener stops the event before it bubbles up to the body.
+        assert!(button.dispatch_event(event.clone()));
+        assert_eq!(seen.borrow().len(), 1);
+        assert_eq!(event.event_phase(), EventPhase::None);
+        assert!(event.current_target().is_none());
+
+        drop(_on_button);
+        drop(_never);
+        let event = Event::new_with_init(EventKind::Click, &init);
+        assert!(!button.dispatch_event(event.clone()));
+        assert!(event.default_prevented());
+        assert_eq!(
+            seen.borrow()[1],
+            ("BUTTON".into(), "BODY".into(), EventPhase::Bubbling)
+        );
+        assert!(Element::try_from(EventTarget::new()).is_err());
+    }
+
+    #[test]
+    fn events() {
+        use futures::{executor::block_on, StreamExt};
+
+        let target = EventTarget::new();
+        let mut keys = target.events(EventKind::KeyDown);
+        let next_click = target.next_event(EventKind::Click);
+        target.dispatch_event(EventKind::KeyDown);
+        target.dispatch_event(EventKind::Click);
+        target.dispatch_event(EventKind::KeyDown);
+        // The `once` listener has already gone, so this isn't sent anywhere.
+        target.dispatch_event(EventKind::Click);
+        assert_eq!(block_on(next_click).kind(), EventKind::Click);
+        assert_eq!(block_on(keys.next()).unwrap().kind(), EventKind::KeyDown);
+        assert_eq!(block_on(keys.next()).unwrap().kind(), EventKind::KeyDown);
+        drop(keys);
+        target.dispatch_event(EventKind::KeyDown);
+    }
+
+    #[test]
+    fn mut_listeners() {
+        let target = EventTarget::new();
+        let mut count = 0;
+        let counted = Rc::new(RefCell::new(0));
+        let counted_copy = counted.clone();
+        let _guard = target.add_event_listener_mut(EventKind::Click, move |_| {
+            count += 1;
+            *counted_copy.borrow_mut() = count;
+        });
+        let _once = target.add_event_listener_with_handle(EventKind::Click, {
+            let counted = counted.clone();
+            move |_, handle| {
+                *counted.borrow_mut() += 100;
+                handle.unsubscribe();
+            }
+        });
+        target.dispatch_event(EventKind::Click);
+        assert_eq!(*counted.borrow(), 101);
+        target.dispatch_event(EventKind::Click);
+        assert_eq!(*counted.borrow(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "was called while it was already running")]
+    fn reentrant_mut_listener() {
+        let target = EventTarget::new();
+        let target_copy = target.clone();
+        let _guard = target.add_event_listener_mut(EventKind::Click, move |_| {
+            // Dispatching a new event, since the current one is still being dispatched.
+            target_copy.dispatch_event(EventKind::Click);
+        });
+        target.dispatch_event(EventKind::Click);
+    }
+
+    #[test]
+    fn once_releases_listener() {
+        let target = EventTarget::new();
+        let state = Rc::new(());
+        let mut options = AddEventListenerOptions::default();
+        options.once(true).passive(true);
+        let guard = target.add_event_listener_opts(
+            EventKind::Click,
+            {
+                let state = state.clone();
+                move |_| {
+                    let _ = &state;
+                }
+            },
+            options,
+        );
+        assert_eq!(guard.kind(), Some(&EventKind::Click));
+        assert_eq!(guard.options().unwrap().passive, Some(true));
+        assert_eq!(Rc::strong_count(&state), 2);
+        target.dispatch_event(EventKind::Click);
+        // The guard is still alive, but the listener has been freed.
+        assert_eq!(Rc::strong_count(&state), 1);
+        drop(guard);
+    }
+}
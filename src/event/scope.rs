@@ -0,0 +1,203 @@
+//! Owning many listeners at once.
+
+use std::{
+    cell::RefCell,
+    fmt,
+    iter::FromIterator,
+    rc::{Rc, Weak},
+};
+
+use super::SubscribeGuard;
+
+/// Owns a set of guards, removing all their listeners when it is dropped.
+///
+/// Scopes can be nested with `child`, so that a component can own its own scope while its
+/// listeners still go away with those of its parent.
+///
+/// ```rust,no_run
+/// use dommer::event::{EventKind, Scope};
+///
+/// let body = dommer::document().body();
+/// let app = Scope::new();
+/// app.add(body.add_event_listener(EventKind::KeyDown, |_| ()));
+///
+/// let dialog = app.child();
+/// dialog.add(body.add_event_listener(EventKind::Click, |_| ()));
+/// // Removes both listeners, even though `dialog` is still alive.
+/// app.clear();
+/// assert!(dialog.is_empty());
+/// ```
+pub struct Scope {
+    inner: Rc<RefCell<Inner>>,
+}
+
+#[derive(Default)]
+struct Inner {
+    guards: Vec<SubscribeGuard>,
+    children: Vec<Weak<RefCell<Inner>>>,
+}
+
+impl Inner {
+    fn len(&self) -> usize {
+        let children: usize = self
+            .children
+            .iter()
+            .filter_map(Weak::upgrade)
+            .map(|child| child.borrow().len())
+            .sum();
+        self.guards.len() + children
+    }
+}
+
+/// Drop the guards in `inner` and its children, outside of any borrow so that unsubscribing can't
+/// find the scope borrowed. The children stay linked, so guards added to them later still go
+/// with this scope.
+fn clear(inner: &RefCell<Inner>) {
+    let (guards, children) = {
+        let mut inner = inner.borrow_mut();
+        inner.children.retain(|child| child.strong_count() > 0);
+        let children: Vec<_> = inner.children.iter().filter_map(Weak::upgrade).collect();
+        (std::mem::take(&mut inner.guards), children)
+    };
+    drop(guards);
+    for child in children {
+        clear(&child);
+    }
+}
+
+impl Scope {
+    pub fn new() -> Self {
+        Scope {
+            inner: Rc::new(RefCell::new(Inner::default())),
+        }
+    }
+
+    /// Keep `guard` until the scope, or one of its ancestors, is dropped or cleared.
+    pub fn add(&self, guard: SubscribeGuard) {
+        self.inner.borrow_mut().guards.push(guard);
+    }
+
+    /// A new scope that is cleared along with this one, as well as when it is dropped itself.
+    pub fn child(&self) -> Scope {
+        let child = Scope::new();
+        let mut inner = self.inner.borrow_mut();
+        inner.children.retain(|child| child.strong_count() > 0);
+        inner.children.push(Rc::downgrade(&child.inner));
+        child
+    }
+
+    /// The number of guards in this scope and its children.
+    pub fn len(&self) -> usize {
+        self.inner.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove all the listeners in this scope and its children now.
+    pub fn clear(&self) {
+        clear(&self.inner);
+    }
+}
+
+impl Default for Scope {
+    fn default() -> Self {
+        Scope::new()
+    }
+}
+
+impl fmt::Debug for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Scope").field("len", &self.len()).finish()
+    }
+}
+
+impl Drop for Scope {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl Extend<SubscribeGuard> for Scope {
+    fn extend<I: IntoIterator<Item = SubscribeGuard>>(&mut self, guards: I) {
+        self.inner.borrow_mut().guards.extend(guards);
+    }
+}
+
+impl FromIterator<SubscribeGuard> for Scope {
+    fn from_iter<I: IntoIterator<Item = SubscribeGuard>>(guards: I) -> Self {
+        let mut scope = Scope::new();
+        scope.extend(guards);
+        scope
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{EventKind, EventTarget};
+    use std::cell::Cell;
+
+    #[test]
+    fn scope() {
+        let target = EventTarget::new();
+        let count = Rc::new(Cell::new(0));
+        let listen = || {
+            let count = count.clone();
+            target.add_event_listener(EventKind::Click, move |_| count.set(count.get() + 1))
+        };
+
+        let parent = Scope::new();
+        parent.add(listen());
+        let child = parent.child();
+        child.add(listen());
+        let grandchild = child.child();
+        grandchild.add(listen());
+        assert_eq!(parent.len(), 3);
+        target.dispatch_event(EventKind::Click);
+        assert_eq!(count.get(), 3);
+
+        // Dropping a child removes its listeners and those of its children, but not its parent's.
+        drop(child);
+        assert_eq!(parent.len(), 1);
+        target.dispatch_event(EventKind::Click);
+        assert_eq!(count.get(), 4);
+
+        let child = parent.child();
+        child.add(listen());
+        drop(parent);
+        assert!(child.is_empty());
+        target.dispatch_event(EventKind::Click);
+        assert_eq!(count.get(), 4);
+
+        // A forgotten guard doesn't remove its listener.
+        listen().forget();
+        target.dispatch_event(EventKind::Click);
+        assert_eq!(count.get(), 5);
+    }
+
+    #[test]
+    fn clear_keeps_children() {
+        let target = EventTarget::new();
+        let count = Rc::new(Cell::new(0));
+        let listen = || {
+            let count = count.clone();
+            target.add_event_listener(EventKind::Click, move |_| count.set(count.get() + 1))
+        };
+
+        let parent = Scope::new();
+        let child = parent.child();
+        child.add(listen());
+        parent.clear();
+        assert!(child.is_empty());
+
+        // The child is still part of its parent after the clear.
+        child.add(listen());
+        assert_eq!(parent.len(), 1);
+        drop(parent);
+        assert!(child.is_empty());
+        target.dispatch_event(EventKind::Click);
+        assert_eq!(count.get(), 0);
+    }
+}
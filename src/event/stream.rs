@@ -0,0 +1,82 @@
+//! Listening for events with futures and streams, instead of callbacks.
+
+use futures::{
+    channel::{mpsc, oneshot},
+    Stream,
+};
+use std::{
+    cell::RefCell,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use super::{Event, SubscribeGuard};
+
+/// The events of one kind fired on a target, from `EventTarget::events`.
+///
+/// Events are queued until they are read. Dropping the stream removes the listener.
+#[derive(Debug)]
+pub struct EventStream {
+    receiver: mpsc::UnboundedReceiver<Event>,
+    _guard: SubscribeGuard,
+}
+
+impl EventStream {
+    pub(super) fn new(subscribe: impl FnOnce(Box<dyn Fn(Event)>) -> SubscribeGuard) -> EventStream {
+        let (sender, receiver) = mpsc::unbounded();
+        let guard = subscribe(Box::new(move |event| {
+            // The receiver is only dropped along with the guard.
+            let _ = sender.unbounded_send(event);
+        }));
+        EventStream {
+            receiver,
+            _guard: guard,
+        }
+    }
+}
+
+impl Stream for EventStream {
+    type Item = Event;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Event>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+/// The next event of one kind fired on a target, from `EventTarget::next_event`.
+///
+/// Dropping the future before the event fires removes the listener.
+#[derive(Debug)]
+pub struct NextEvent {
+    receiver: oneshot::Receiver<Event>,
+    _guard: SubscribeGuard,
+}
+
+impl NextEvent {
+    pub(super) fn new(subscribe: impl FnOnce(Box<dyn Fn(Event)>) -> SubscribeGuard) -> NextEvent {
+        let (sender, receiver) = oneshot::channel();
+        let sender = RefCell::new(Some(sender));
+        let guard = subscribe(Box::new(move |event| {
+            if let Some(sender) = sender.borrow_mut().take() {
+                let _ = sender.send(event);
+            }
+        }));
+        NextEvent {
+            receiver,
+            _guard: guard,
+        }
+    }
+}
+
+impl Future for NextEvent {
+    type Output = Event;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Event> {
+        match Pin::new(&mut self.receiver).poll(cx) {
+            Poll::Ready(Ok(event)) => Poll::Ready(event),
+            // The listener is kept until the event fires, so the sender can't be dropped first.
+            Poll::Ready(Err(_)) | Poll::Pending => Poll::Pending,
+        }
+    }
+}
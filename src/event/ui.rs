@@ -0,0 +1,1071 @@
+//! Typed wrappers for the events fired by user input: mice, pointers, wheels, keyboards, focus
+//! changes, text input and composition.
+//!
+//! Each wrapper holds the `Event` it came from, along with a copy of the extra fields for its
+//! kind, taken when the event is downcast. Get one with `Event::downcast`:
+//!
+//! ```rust,no_run
+//! use dommer::event::{EventKind, MouseEvent};
+//!
+//! let body = dommer::document().body();
+//! let _guard = body.add_event_listener(EventKind::Click, |event| {
+//!     if let Ok(event) = event.downcast::<MouseEvent>() {
+//!         dommer::println!("clicked at ({}, {})", event.client_x(), event.client_y());
+//!     }
+//! });
+//! ```
+
+use std::{any::Any, borrow::Cow, ops::Deref, rc::Rc};
+use wasm_bindgen::JsCast;
+
+use super::{custom, Event, EventInit, EventKind, EventTarget};
+use crate::{
+    backend::{self, web::WebEvent},
+    error::DomException,
+};
+
+bitflags::bitflags! {
+    /// The modifier keys held down when an event fired.
+    #[derive(Default)]
+    pub struct Modifiers: u8 {
+        const ALT = 1;
+        const CONTROL = 1 << 1;
+        const META = 1 << 2;
+        const SHIFT = 1 << 3;
+    }
+}
+
+impl Modifiers {
+    fn from_keys(alt: bool, control: bool, meta: bool, shift: bool) -> Self {
+        let mut modifiers = Modifiers::empty();
+        modifiers.set(Modifiers::ALT, alt);
+        modifiers.set(Modifiers::CONTROL, control);
+        modifiers.set(Modifiers::META, meta);
+        modifiers.set(Modifiers::SHIFT, shift);
+        modifiers
+    }
+}
+
+bitflags::bitflags! {
+    /// The mouse buttons held down when an event fired (`MouseEvent.buttons`).
+    #[derive(Default)]
+    pub struct MouseButtons: u16 {
+        const PRIMARY = 1;
+        const SECONDARY = 1 << 1;
+        const AUXILIARY = 1 << 2;
+        const BACK = 1 << 3;
+        const FORWARD = 1 << 4;
+    }
+}
+
+/// The mouse button that changed state (`MouseEvent.button`).
+///
+/// Note that the numbering is different to `MouseButtons`: the auxiliary (usually middle) button
+/// is 1 and the secondary button is 2.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum MouseButton {
+    Primary,
+    Auxiliary,
+    Secondary,
+    Back,
+    Forward,
+    Other(i16),
+}
+
+impl Default for MouseButton {
+    fn default() -> Self {
+        MouseButton::Primary
+    }
+}
+
+impl From<i16> for MouseButton {
+    fn from(button: i16) -> Self {
+        match button {
+            0 => MouseButton::Primary,
+            1 => MouseButton::Auxiliary,
+            2 => MouseButton::Secondary,
+            3 => MouseButton::Back,
+            4 => MouseButton::Forward,
+            other => MouseButton::Other(other),
+        }
+    }
+}
+
+impl From<MouseButton> for i16 {
+    fn from(button: MouseButton) -> i16 {
+        match button {
+            MouseButton::Primary => 0,
+            MouseButton::Auxiliary => 1,
+            MouseButton::Secondary => 2,
+            MouseButton::Back => 3,
+            MouseButton::Forward => 4,
+            MouseButton::Other(other) => other,
+        }
+    }
+}
+
+/// The kind of device behind a `PointerEvent`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum PointerType {
+    Mouse,
+    Pen,
+    Touch,
+    Other(String),
+}
+
+impl Default for PointerType {
+    fn default() -> Self {
+        PointerType::Mouse
+    }
+}
+
+impl From<String> for PointerType {
+    fn from(pointer_type: String) -> Self {
+        match pointer_type.as_str() {
+            "mouse" => PointerType::Mouse,
+            "pen" => PointerType::Pen,
+            "touch" => PointerType::Touch,
+            _ => PointerType::Other(pointer_type),
+        }
+    }
+}
+
+impl PointerType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            PointerType::Mouse => "mouse",
+            PointerType::Pen => "pen",
+            PointerType::Touch => "touch",
+            PointerType::Other(other) => other,
+        }
+    }
+}
+
+/// The unit of the deltas in a `WheelEvent`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum DeltaMode {
+    Pixel,
+    Line,
+    Page,
+}
+
+impl Default for DeltaMode {
+    fn default() -> Self {
+        DeltaMode::Pixel
+    }
+}
+
+impl DeltaMode {
+    fn from_u32(mode: u32) -> Self {
+        match mode {
+            1 => DeltaMode::Line,
+            2 => DeltaMode::Page,
+            _ => DeltaMode::Pixel,
+        }
+    }
+
+    fn to_u32(self) -> u32 {
+        match self {
+            DeltaMode::Pixel => 0,
+            DeltaMode::Line => 1,
+            DeltaMode::Page => 2,
+        }
+    }
+}
+
+/// Where on the keyboard a key is, for keys that appear more than once.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum KeyLocation {
+    Standard,
+    Left,
+    Right,
+    Numpad,
+}
+
+impl Default for KeyLocation {
+    fn default() -> Self {
+        KeyLocation::Standard
+    }
+}
+
+impl KeyLocation {
+    fn from_u32(location: u32) -> Self {
+        match location {
+            1 => KeyLocation::Left,
+            2 => KeyLocation::Right,
+            3 => KeyLocation::Numpad,
+            _ => KeyLocation::Standard,
+        }
+    }
+
+    fn to_u32(self) -> u32 {
+        match self {
+            KeyLocation::Standard => 0,
+            KeyLocation::Left => 1,
+            KeyLocation::Right => 2,
+            KeyLocation::Numpad => 3,
+        }
+    }
+}
+
+/// The fields of a `MouseEvent`, also used to create one.
+#[derive(Debug, Clone, Default)]
+pub struct MouseEventData {
+    /// The click count for clicks, 0 otherwise.
+    pub detail: i32,
+    pub screen_x: i32,
+    pub screen_y: i32,
+    pub client_x: i32,
+    pub client_y: i32,
+    /// Only set by the browser, creating an event ignores it.
+    pub page_x: i32,
+    /// Only set by the browser, creating an event ignores it.
+    pub page_y: i32,
+    /// Only set by the browser, creating an event ignores it.
+    pub offset_x: i32,
+    /// Only set by the browser, creating an event ignores it.
+    pub offset_y: i32,
+    pub movement_x: i32,
+    pub movement_y: i32,
+    pub button: MouseButton,
+    pub buttons: MouseButtons,
+    pub modifiers: Modifiers,
+    pub related_target: Option<EventTarget>,
+}
+
+/// The fields a `PointerEvent` has on top of those of a `MouseEvent`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PointerEventData {
+    pub pointer_id: i32,
+    pub width: i32,
+    pub height: i32,
+    pub pressure: f32,
+    pub tangential_pressure: f32,
+    pub tilt_x: i32,
+    pub tilt_y: i32,
+    pub twist: i32,
+    pub pointer_type: PointerType,
+    pub is_primary: bool,
+}
+
+/// The fields a `WheelEvent` has on top of those of a `MouseEvent`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WheelEventData {
+    pub delta_x: f64,
+    pub delta_y: f64,
+    pub delta_z: f64,
+    pub delta_mode: DeltaMode,
+}
+
+/// The fields of a `KeyboardEvent`.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct KeyboardEventData {
+    /// The value of the key, taking the keyboard layout and modifiers into account, e.g. `"a"`,
+    /// `"A"` or `"Enter"`.
+    pub key: String,
+    /// The physical key, whatever the layout, e.g. `"KeyA"`.
+    pub code: String,
+    pub location: KeyLocation,
+    pub repeat: bool,
+    pub is_composing: bool,
+    pub modifiers: Modifiers,
+}
+
+/// The fields of a `FocusEvent`.
+#[derive(Debug, Clone, Default)]
+pub struct FocusEventData {
+    /// The target losing focus for focus events, or gaining it for blur events.
+    pub related_target: Option<EventTarget>,
+}
+
+/// The fields of an `InputEvent`.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct InputEventData {
+    pub data: Option<String>,
+    /// What kind of change this is, e.g. `"insertText"` or `"deleteContentBackward"`.
+    pub input_type: String,
+    pub is_composing: bool,
+}
+
+/// The fields of a `CompositionEvent`.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct CompositionEventData {
+    pub data: Option<String>,
+}
+
+/// The extra fields an event has, depending on its interface.
+///
+/// Backends report this with `EventHandle::data`, and are passed it to create events.
+#[derive(Debug, Clone)]
+pub enum EventData {
+    /// A plain `Event`, or an interface without a wrapper.
+    Plain,
+    Mouse(MouseEventData),
+    Pointer(MouseEventData, PointerEventData),
+    Wheel(MouseEventData, WheelEventData),
+    Keyboard(KeyboardEventData),
+    Focus(FocusEventData),
+    Input(InputEventData),
+    Composition(CompositionEventData),
+    /// The detail of a `CustomEvent`.
+    Custom(Rc<dyn Any>),
+}
+
+impl Default for EventData {
+    fn default() -> Self {
+        EventData::Plain
+    }
+}
+
+/// A type an `Event` can be checked and converted into, with `Event::downcast`.
+pub trait FromEvent: Sized {
+    /// Gives the event back if it is the wrong kind.
+    fn from_event(event: Event) -> Result<Self, Event>;
+}
+
+impl FromEvent for Event {
+    fn from_event(event: Event) -> Result<Self, Event> {
+        Ok(event)
+    }
+}
+
+pub(super) fn create(
+    kind: EventKind,
+    init: &EventInit,
+    data: &EventData,
+) -> Result<Event, DomException> {
+    let raw = backend::current().create_event(&Cow::from(kind), init, data)?;
+    Ok(Event::from_handle(raw))
+}
+
+/// Implement the conversions shared by all the wrappers.
+macro_rules! wrapper {
+    ($name:ident, $web_sys:ident, $target:ty, $field:ident) => {
+        impl Deref for $name {
+            type Target = $target;
+
+            fn deref(&self) -> &$target {
+                &self.$field
+            }
+        }
+
+        impl From<$name> for Event {
+            fn from(event: $name) -> Event {
+                Event::from(event.$field)
+            }
+        }
+
+        impl $name {
+            /// The underlying `web_sys` event, if this event comes from the browser.
+            pub fn as_web_sys(&self) -> Option<&web_sys::$web_sys> {
+                let event: &Event = self;
+                event.as_web_sys().map(|event| event.unchecked_ref())
+            }
+        }
+    };
+}
+
+/// An event fired by a mouse, e.g. `click` or `mousemove`.
+#[derive(Debug, Clone)]
+pub struct MouseEvent {
+    event: Event,
+    data: MouseEventData,
+}
+
+wrapper!(MouseEvent, MouseEvent, Event, event);
+
+impl MouseEvent {
+    pub fn new(kind: EventKind, init: &EventInit, data: MouseEventData) -> Self {
+        expect!(
+            MouseEvent::try_new(kind, init, data),
+            "creating a `MouseEvent`"
+        )
+    }
+
+    pub fn try_new(
+        kind: EventKind,
+        init: &EventInit,
+        data: MouseEventData,
+    ) -> Result<Self, DomException> {
+        let data = EventData::Mouse(data);
+        let event = create(kind, init, &data)?;
+        match data {
+            EventData::Mouse(data) => Ok(MouseEvent { event, data }),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn detail(&self) -> i32 {
+        self.data.detail
+    }
+
+    pub fn screen_x(&self) -> i32 {
+        self.data.screen_x
+    }
+
+    pub fn screen_y(&self) -> i32 {
+        self.data.screen_y
+    }
+
+    /// The x coordinate relative to the viewport.
+    pub fn client_x(&self) -> i32 {
+        self.data.client_x
+    }
+
+    /// The y coordinate relative to the viewport.
+    pub fn client_y(&self) -> i32 {
+        self.data.client_y
+    }
+
+    /// The x coordinate relative to the whole document.
+    pub fn page_x(&self) -> i32 {
+        self.data.page_x
+    }
+
+    /// The y coordinate relative to the whole document.
+    pub fn page_y(&self) -> i32 {
+        self.data.page_y
+    }
+
+    /// The x coordinate relative to the padding edge of the target.
+    pub fn offset_x(&self) -> i32 {
+        self.data.offset_x
+    }
+
+    /// The y coordinate relative to the padding edge of the target.
+    pub fn offset_y(&self) -> i32 {
+        self.data.offset_y
+    }
+
+    /// How far the mouse moved since the last `mousemove`.
+    pub fn movement_x(&self) -> i32 {
+        self.data.movement_x
+    }
+
+    /// How far the mouse moved since the last `mousemove`.
+    pub fn movement_y(&self) -> i32 {
+        self.data.movement_y
+    }
+
+    pub fn button(&self) -> MouseButton {
+        self.data.button
+    }
+
+    pub fn buttons(&self) -> MouseButtons {
+        self.data.buttons
+    }
+
+    pub fn modifiers(&self) -> Modifiers {
+        self.data.modifiers
+    }
+
+    /// The target the mouse came from or is going to, for `mouseover`, `mouseout`,
+    /// `mouseenter` and `mouseleave`.
+    pub fn related_target(&self) -> Option<&EventTarget> {
+        self.data.related_target.as_ref()
+    }
+}
+
+impl FromEvent for MouseEvent {
+    fn from_event(event: Event) -> Result<Self, Event> {
+        match event.raw.data() {
+            EventData::Mouse(data) | EventData::Pointer(data, _) | EventData::Wheel(data, _) => {
+                Ok(MouseEvent { event, data })
+            }
+            _ => Err(event),
+        }
+    }
+}
+
+/// An event fired by a mouse, pen or touch, e.g. `pointerdown`.
+#[derive(Debug, Clone)]
+pub struct PointerEvent {
+    mouse: MouseEvent,
+    data: PointerEventData,
+}
+
+wrapper!(PointerEvent, PointerEvent, MouseEvent, mouse);
+
+impl PointerEvent {
+    pub fn new(
+        kind: EventKind,
+        init: &EventInit,
+        mouse: MouseEventData,
+        data: PointerEventData,
+    ) -> Self {
+        expect!(
+            PointerEvent::try_new(kind, init, mouse, data),
+            "creating a `PointerEvent`"
+        )
+    }
+
+    pub fn try_new(
+        kind: EventKind,
+        init: &EventInit,
+        mouse: MouseEventData,
+        data: PointerEventData,
+    ) -> Result<Self, DomException> {
+        let data = EventData::Pointer(mouse, data);
+        let event = create(kind, init, &data)?;
+        match data {
+            EventData::Pointer(mouse, data) => Ok(PointerEvent {
+                mouse: MouseEvent { event, data: mouse },
+                data,
+            }),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn pointer_id(&self) -> i32 {
+        self.data.pointer_id
+    }
+
+    pub fn width(&self) -> i32 {
+        self.data.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.data.height
+    }
+
+    /// Between 0 and 1.
+    pub fn pressure(&self) -> f32 {
+        self.data.pressure
+    }
+
+    /// Between -1 and 1.
+    pub fn tangential_pressure(&self) -> f32 {
+        self.data.tangential_pressure
+    }
+
+    /// In degrees, between -90 and 90.
+    pub fn tilt_x(&self) -> i32 {
+        self.data.tilt_x
+    }
+
+    /// In degrees, between -90 and 90.
+    pub fn tilt_y(&self) -> i32 {
+        self.data.tilt_y
+    }
+
+    /// In degrees, between 0 and 359.
+    pub fn twist(&self) -> i32 {
+        self.data.twist
+    }
+
+    pub fn pointer_type(&self) -> &PointerType {
+        &self.data.pointer_type
+    }
+
+    pub fn is_primary(&self) -> bool {
+        self.data.is_primary
+    }
+}
+
+impl FromEvent for PointerEvent {
+    fn from_event(event: Event) -> Result<Self, Event> {
+        match event.raw.data() {
+            EventData::Pointer(mouse, data) => Ok(PointerEvent {
+                mouse: MouseEvent { event, data: mouse },
+                data,
+            }),
+            _ => Err(event),
+        }
+    }
+}
+
+/// An event fired by a mouse wheel or touchpad scroll.
+#[derive(Debug, Clone)]
+pub struct WheelEvent {
+    mouse: MouseEvent,
+    data: WheelEventData,
+}
+
+wrapper!(WheelEvent, WheelEvent, MouseEvent, mouse);
+
+impl WheelEvent {
+    pub fn new(
+        kind: EventKind,
+        init: &EventInit,
+        mouse: MouseEventData,
+        data: WheelEventData,
+    ) -> Self {
+        expect!(
+            WheelEvent::try_new(kind, init, mouse, data),
+            "creating a `WheelEvent`"
+        )
+    }
+
+    pub fn try_new(
+        kind: EventKind,
+        init: &EventInit,
+        mouse: MouseEventData,
+        data: WheelEventData,
+    ) -> Result<Self, DomException> {
+        let data = EventData::Wheel(mouse, data);
+        let event = create(kind, init, &data)?;
+        match data {
+            EventData::Wheel(mouse, data) => Ok(WheelEvent {
+                mouse: MouseEvent { event, data: mouse },
+                data,
+            }),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn delta_x(&self) -> f64 {
+        self.data.delta_x
+    }
+
+    pub fn delta_y(&self) -> f64 {
+        self.data.delta_y
+    }
+
+    pub fn delta_z(&self) -> f64 {
+        self.data.delta_z
+    }
+
+    /// The unit of the deltas.
+    pub fn delta_mode(&self) -> DeltaMode {
+        self.data.delta_mode
+    }
+}
+
+impl FromEvent for WheelEvent {
+    fn from_event(event: Event) -> Result<Self, Event> {
+        match event.raw.data() {
+            EventData::Wheel(mouse, data) => Ok(WheelEvent {
+                mouse: MouseEvent { event, data: mouse },
+                data,
+            }),
+            _ => Err(event),
+        }
+    }
+}
+
+/// An event fired by a keyboard, e.g. `keydown`.
+#[derive(Debug, Clone)]
+pub struct KeyboardEvent {
+    event: Event,
+    data: KeyboardEventData,
+}
+
+wrapper!(KeyboardEvent, KeyboardEvent, Event, event);
+
+impl KeyboardEvent {
+    pub fn new(kind: EventKind, init: &EventInit, data: KeyboardEventData) -> Self {
+        expect!(
+            KeyboardEvent::try_new(kind, init, data),
+            "creating a `KeyboardEvent`"
+        )
+    }
+
+    pub fn try_new(
+        kind: EventKind,
+        init: &EventInit,
+        data: KeyboardEventData,
+    ) -> Result<Self, DomException> {
+        let data = EventData::Keyboard(data);
+        let event = create(kind, init, &data)?;
+        match data {
+            EventData::Keyboard(data) => Ok(KeyboardEvent { event, data }),
+            _ => unreachable!(),
+        }
+    }
+
+    pub fn key(&self) -> &str {
+        &self.data.key
+    }
+
+    pub fn code(&self) -> &str {
+        &self.data.code
+    }
+
+    pub fn location(&self) -> KeyLocation {
+        self.data.location
+    }
+
+    /// Whether the key is being held down, so this event is an auto-repeat.
+    pub fn repeat(&self) -> bool {
+        self.data.repeat
+    }
+
+    pub fn is_composing(&self) -> bool {
+        self.data.is_composing
+    }
+
+    pub fn modifiers(&self) -> Modifiers {
+        self.data.modifiers
+    }
+}
+
+impl FromEvent for KeyboardEvent {
+    fn from_event(event: Event) -> Result<Self, Event> {
+        match event.raw.data() {
+            EventData::Keyboard(data) => Ok(KeyboardEvent { event, data }),
+            _ => Err(event),
+        }
+    }
+}
+
+/// An event fired when focus moves, e.g. `focus` or `blur`.
+#[derive(Debug, Clone)]
+pub struct FocusEvent {
+    event: Event,
+    data: FocusEventData,
+}
+
+wrapper!(FocusEvent, FocusEvent, Event, event);
+
+impl FocusEvent {
+    pub fn new(kind: EventKind, init: &EventInit, data: FocusEventData) -> Self {
+        expect!(
+            FocusEvent::try_new(kind, init, data),
+            "creating a `FocusEvent`"
+        )
+    }
+
+    pub fn try_new(
+        kind: EventKind,
+        init: &EventInit,
+        data: FocusEventData,
+    ) -> Result<Self, DomException> {
+        let data = EventData::Focus(data);
+        let event = create(kind, init, &data)?;
+        match data {
+            EventData::Focus(data) => Ok(FocusEvent { event, data }),
+            _ => unreachable!(),
+        }
+    }
+
+    /// The other target involved in the focus change, if it is not hidden for privacy reasons.
+    pub fn related_target(&self) -> Option<&EventTarget> {
+        self.data.related_target.as_ref()
+    }
+}
+
+impl FromEvent for FocusEvent {
+    fn from_event(event: Event) -> Result<Self, Event> {
+        match event.raw.data() {
+            EventData::Focus(data) => Ok(FocusEvent { event, data }),
+            _ => Err(event),
+        }
+    }
+}
+
+/// An event fired when editable content changes, e.g. `input` or `beforeinput`.
+#[derive(Debug, Clone)]
+pub struct InputEvent {
+    event: Event,
+    data: InputEventData,
+}
+
+wrapper!(InputEvent, InputEvent, Event, event);
+
+impl InputEvent {
+    pub fn new(kind: EventKind, init: &EventInit, data: InputEventData) -> Self {
+        expect!(
+            InputEvent::try_new(kind, init, data),
+            "creating an `InputEvent`"
+        )
+    }
+
+    pub fn try_new(
+        kind: EventKind,
+        init: &EventInit,
+        data: InputEventData,
+    ) -> Result<Self, DomException> {
+        let data = EventData::Input(data);
+        let event = create(kind, init, &data)?;
+        match data {
+            EventData::Input(data) => Ok(InputEvent { event, data }),
+            _ => unreachable!(),
+        }
+    }
+
+    /// The inserted text, if there is any.
+    pub fn data(&self) -> Option<&str> {
+        self.data.data.as_deref()
+    }
+
+    pub fn input_type(&self) -> &str {
+        &self.data.input_type
+    }
+
+    pub fn is_composing(&self) -> bool {
+        self.data.is_composing
+    }
+}
+
+impl FromEvent for InputEvent {
+    fn from_event(event: Event) -> Result<Self, Event> {
+        match event.raw.data() {
+            EventData::Input(data) => Ok(InputEvent { event, data }),
+            _ => Err(event),
+        }
+    }
+}
+
+/// An event fired while text is entered with an input method, e.g. `compositionupdate`.
+#[derive(Debug, Clone)]
+pub struct CompositionEvent {
+    event: Event,
+    data: CompositionEventData,
+}
+
+wrapper!(CompositionEvent, CompositionEvent, Event, event);
+
+impl CompositionEvent {
+    pub fn new(kind: EventKind, init: &EventInit, data: CompositionEventData) -> Self {
+        expect!(
+            CompositionEvent::try_new(kind, init, data),
+            "creating a `CompositionEvent`"
+        )
+    }
+
+    pub fn try_new(
+        kind: EventKind,
+        init: &EventInit,
+        data: CompositionEventData,
+    ) -> Result<Self, DomException> {
+        let data = EventData::Composition(data);
+        let event = create(kind, init, &data)?;
+        match data {
+            EventData::Composition(data) => Ok(CompositionEvent { event, data }),
+            _ => unreachable!(),
+        }
+    }
+
+    /// The text being composed.
+    pub fn data(&self) -> Option<&str> {
+        self.data.data.as_deref()
+    }
+}
+
+impl FromEvent for CompositionEvent {
+    fn from_event(event: Event) -> Result<Self, Event> {
+        match event.raw.data() {
+            EventData::Composition(data) => Ok(CompositionEvent { event, data }),
+            _ => Err(event),
+        }
+    }
+}
+
+/// Read the fields for the interface a browser event implements. The detail of a `CustomEvent` is
+/// found by `WebEvent::new` instead.
+pub(crate) fn data_from_web_sys(event: &web_sys::Event) -> EventData {
+    use crate::backend::web::WebHandle;
+
+    fn mouse(event: &web_sys::MouseEvent) -> MouseEventData {
+        MouseEventData {
+            detail: event.detail(),
+            screen_x: event.screen_x(),
+            screen_y: event.screen_y(),
+            client_x: event.client_x(),
+            client_y: event.client_y(),
+            page_x: event.page_x(),
+            page_y: event.page_y(),
+            offset_x: event.offset_x(),
+            offset_y: event.offset_y(),
+            movement_x: event.movement_x(),
+            movement_y: event.movement_y(),
+            button: event.button().into(),
+            buttons: MouseButtons::from_bits_truncate(event.buttons()),
+            modifiers: Modifiers::from_keys(
+                event.alt_key(),
+                event.ctrl_key(),
+                event.meta_key(),
+                event.shift_key(),
+            ),
+            related_target: event
+                .related_target()
+                .map(|target| EventTarget::from_handle(WebHandle::wrap(target))),
+        }
+    }
+
+    if let Some(event) = event.dyn_ref::<web_sys::PointerEvent>() {
+        EventData::Pointer(
+            mouse(event),
+            PointerEventData {
+                pointer_id: event.pointer_id(),
+                width: event.width(),
+                height: event.height(),
+                pressure: event.pressure(),
+                tangential_pressure: event.tangential_pressure(),
+                tilt_x: event.tilt_x(),
+                tilt_y: event.tilt_y(),
+                twist: event.twist(),
+                pointer_type: event.pointer_type().into(),
+                is_primary: event.is_primary(),
+            },
+        )
+    } else if let Some(event) = event.dyn_ref::<web_sys::WheelEvent>() {
+        EventData::Wheel(
+            mouse(event),
+            WheelEventData {
+                delta_x: event.delta_x(),
+                delta_y: event.delta_y(),
+                delta_z: event.delta_z(),
+                delta_mode: DeltaMode::from_u32(event.delta_mode()),
+            },
+        )
+    } else if let Some(event) = event.dyn_ref::<web_sys::MouseEvent>() {
+        EventData::Mouse(mouse(event))
+    } else if let Some(event) = event.dyn_ref::<web_sys::KeyboardEvent>() {
+        EventData::Keyboard(KeyboardEventData {
+            key: event.key(),
+            code: event.code(),
+            location: KeyLocation::from_u32(event.location()),
+            repeat: event.repeat(),
+            is_composing: event.is_composing(),
+            modifiers: Modifiers::from_keys(
+                event.alt_key(),
+                event.ctrl_key(),
+                event.meta_key(),
+                event.shift_key(),
+            ),
+        })
+    } else if let Some(event) = event.dyn_ref::<web_sys::FocusEvent>() {
+        EventData::Focus(FocusEventData {
+            related_target: event
+                .related_target()
+                .map(|target| EventTarget::from_handle(WebHandle::wrap(target))),
+        })
+    } else if let Some(event) = event.dyn_ref::<web_sys::InputEvent>() {
+        EventData::Input(InputEventData {
+            data: event.data(),
+            input_type: event.input_type(),
+            is_composing: event.is_composing(),
+        })
+    } else if let Some(event) = event.dyn_ref::<web_sys::CompositionEvent>() {
+        EventData::Composition(CompositionEventData { data: event.data() })
+    } else {
+        EventData::Plain
+    }
+}
+
+/// Create a browser event of the interface matching `data`.
+pub(crate) fn new_web_sys(
+    kind: &str,
+    init: &EventInit,
+    data: &EventData,
+) -> Result<WebEvent, wasm_bindgen::JsValue> {
+    use crate::backend::web::WebHandle;
+    use wasm_bindgen::JsValue;
+
+    let dict = js_sys::Object::new();
+    let set = |key: &str, value: JsValue| {
+        // Setting a property on a plain object can't fail.
+        let _ = js_sys::Reflect::set(&dict, &JsValue::from_str(key), &value);
+    };
+    let set_related_target = |target: &Option<EventTarget>| {
+        let target = target
+            .as_ref()
+            .and_then(|target| target.raw.as_any().downcast_ref::<WebHandle>());
+        if let Some(target) = target {
+            set("relatedTarget", target.as_web_sys().into());
+        }
+    };
+    let set_modifiers = |modifiers: Modifiers| {
+        set("altKey", modifiers.contains(Modifiers::ALT).into());
+        set("ctrlKey", modifiers.contains(Modifiers::CONTROL).into());
+        set("metaKey", modifiers.contains(Modifiers::META).into());
+        set("shiftKey", modifiers.contains(Modifiers::SHIFT).into());
+    };
+    let set_mouse = |mouse: &MouseEventData| {
+        set("detail", mouse.detail.into());
+        set("screenX", mouse.screen_x.into());
+        set("screenY", mouse.screen_y.into());
+        set("clientX", mouse.client_x.into());
+        set("clientY", mouse.client_y.into());
+        set("movementX", mouse.movement_x.into());
+        set("movementY", mouse.movement_y.into());
+        set("button", i16::from(mouse.button).into());
+        set("buttons", mouse.buttons.bits().into());
+        set_modifiers(mouse.modifiers);
+        set_related_target(&mouse.related_target);
+    };
+
+    if let Some(bubbles) = init.bubbles {
+        set("bubbles", bubbles.into());
+    }
+    if let Some(cancelable) = init.cancelable {
+        set("cancelable", cancelable.into());
+    }
+    if let Some(composed) = init.composed {
+        set("composed", composed.into());
+    }
+
+    let mut payload = None;
+    let inner = match data {
+        EventData::Plain => web_sys::Event::new_with_event_init_dict(kind, dict.unchecked_ref())?,
+        EventData::Mouse(mouse) => {
+            set_mouse(mouse);
+            web_sys::MouseEvent::new_with_mouse_event_init_dict(kind, dict.unchecked_ref())?.into()
+        }
+        EventData::Pointer(mouse, pointer) => {
+            set_mouse(mouse);
+            set("pointerId", pointer.pointer_id.into());
+            set("width", pointer.width.into());
+            set("height", pointer.height.into());
+            set("pressure", pointer.pressure.into());
+            set("tangentialPressure", pointer.tangential_pressure.into());
+            set("tiltX", pointer.tilt_x.into());
+            set("tiltY", pointer.tilt_y.into());
+            set("twist", pointer.twist.into());
+            set("pointerType", pointer.pointer_type.as_str().into());
+            set("isPrimary", pointer.is_primary.into());
+            web_sys::PointerEvent::new_with_event_init_dict(kind, dict.unchecked_ref())?.into()
+        }
+        EventData::Wheel(mouse, wheel) => {
+            set_mouse(mouse);
+            set("deltaX", wheel.delta_x.into());
+            set("deltaY", wheel.delta_y.into());
+            set("deltaZ", wheel.delta_z.into());
+            set("deltaMode", wheel.delta_mode.to_u32().into());
+            web_sys::WheelEvent::new_with_event_init_dict(kind, dict.unchecked_ref())?.into()
+        }
+        EventData::Keyboard(keyboard) => {
+            set("key", keyboard.key.as_str().into());
+            set("code", keyboard.code.as_str().into());
+            set("location", keyboard.location.to_u32().into());
+            set("repeat", keyboard.repeat.into());
+            set("isComposing", keyboard.is_composing.into());
+            set_modifiers(keyboard.modifiers);
+            web_sys::KeyboardEvent::new_with_keyboard_event_init_dict(kind, dict.unchecked_ref())?
+                .into()
+        }
+        EventData::Focus(focus) => {
+            set_related_target(&focus.related_target);
+            web_sys::FocusEvent::new_with_focus_event_init_dict(kind, dict.unchecked_ref())?.into()
+        }
+        EventData::Input(input) => {
+            if let Some(data) = &input.data {
+                set("data", data.as_str().into());
+            }
+            set("inputType", input.input_type.as_str().into());
+            set("isComposing", input.is_composing.into());
+            web_sys::InputEvent::new_with_event_init_dict(kind, dict.unchecked_ref())?.into()
+        }
+        EventData::Composition(composition) => {
+            if let Some(data) = &composition.data {
+                set("data", data.as_str().into());
+            }
+            web_sys::CompositionEvent::new_with_event_init_dict(kind, dict.unchecked_ref())?.into()
+        }
+        EventData::Custom(detail) => {
+            let (detail, guard) = custom::register(detail.clone());
+            set("detail", detail);
+            payload = Some(Rc::new(guard));
+            web_sys::CustomEvent::new_with_event_init_dict(kind, dict.unchecked_ref())?.into()
+        }
+    };
+    Ok(WebEvent {
+        inner,
+        detail: match data {
+            EventData::Custom(detail) => Some(detail.clone()),
+            _ => None,
+        },
+        _payload: payload,
+    })
+}
@@ -0,0 +1,315 @@
+//! The kinds of body a request can send.
+
+use bytes::Bytes;
+use http::header::HeaderValue;
+use wasm_bindgen::JsValue;
+
+use crate::error::DomException;
+
+/// The body of a request.
+///
+/// Each kind of body comes with a `Content-Type`, which is sent unless the request sets its own.
+#[derive(Debug, Clone)]
+pub enum Body {
+    /// Raw bytes, sent as `application/octet-stream`.
+    Bytes(Bytes),
+    /// Text, sent as `text/plain;charset=UTF-8`.
+    Text(String),
+    /// Json text, sent as `application/json`. Usually made with `Body::json`.
+    Json(String),
+    /// A form, sent as `multipart/form-data`.
+    FormData(FormData),
+    /// A url-encoded form, sent as `application/x-www-form-urlencoded;charset=UTF-8`.
+    UrlSearchParams(UrlSearchParams),
+    /// A blob, or a file from an `<input type="file">`, sent with the blob's type.
+    Blob(web_sys::Blob),
+}
+
+impl Body {
+    /// Serialize `value` as json.
+    #[cfg(feature = "serde")]
+    pub fn json<T>(value: &T) -> Result<Body, serde_json::Error>
+    where
+        T: serde_crate::Serialize + ?Sized,
+    {
+        serde_json::to_string(value).map(Body::Json)
+    }
+
+    /// Whether this is an empty string or an empty array of bytes.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Body::Bytes(bytes) => bytes.is_empty(),
+            Body::Text(text) | Body::Json(text) => text.is_empty(),
+            Body::FormData(_) | Body::UrlSearchParams(_) | Body::Blob(_) => false,
+        }
+    }
+
+    /// The `Content-Type` to send with the body.
+    ///
+    /// This is `None` where the browser picks the type itself, which is for forms (so it can
+    /// choose the boundary) and blobs.
+    pub fn content_type(&self) -> Option<HeaderValue> {
+        let content_type = match self {
+            Body::Bytes(_) => "application/octet-stream",
+            Body::Text(_) => "text/plain;charset=UTF-8",
+            Body::Json(_) => "application/json",
+            Body::UrlSearchParams(_) => "application/x-www-form-urlencoded;charset=UTF-8",
+            Body::FormData(_) | Body::Blob(_) => return None,
+        };
+        Some(HeaderValue::from_static(content_type))
+    }
+
+    pub(crate) fn to_js(&self) -> Result<JsValue, DomException> {
+        Ok(match self {
+            Body::Bytes(bytes) => js_sys::Uint8Array::from(&bytes[..]).into(),
+            Body::Text(text) | Body::Json(text) => JsValue::from_str(text),
+            Body::FormData(form) => form.to_web_sys()?.into(),
+            Body::UrlSearchParams(params) => JsValue::from_str(&params.to_string()),
+            Body::Blob(blob) => blob.clone().into(),
+        })
+    }
+}
+
+impl From<Bytes> for Body {
+    fn from(bytes: Bytes) -> Self {
+        Body::Bytes(bytes)
+    }
+}
+
+impl From<Vec<u8>> for Body {
+    fn from(bytes: Vec<u8>) -> Self {
+        Body::Bytes(bytes.into())
+    }
+}
+
+impl<'a> From<&'a [u8]> for Body {
+    fn from(bytes: &'a [u8]) -> Self {
+        Body::Bytes(bytes.into())
+    }
+}
+
+impl From<String> for Body {
+    fn from(text: String) -> Self {
+        Body::Text(text)
+    }
+}
+
+impl<'a> From<&'a str> for Body {
+    fn from(text: &'a str) -> Self {
+        Body::Text(text.to_owned())
+    }
+}
+
+impl From<FormData> for Body {
+    fn from(form: FormData) -> Self {
+        Body::FormData(form)
+    }
+}
+
+impl From<UrlSearchParams> for Body {
+    fn from(params: UrlSearchParams) -> Self {
+        Body::UrlSearchParams(params)
+    }
+}
+
+impl From<web_sys::Blob> for Body {
+    fn from(blob: web_sys::Blob) -> Self {
+        Body::Blob(blob)
+    }
+}
+
+/// A form with text fields and files, built in rust and converted to a javascript `FormData`
+/// when the request is built.
+#[derive(Debug, Clone, Default)]
+pub struct FormData {
+    parts: Vec<(String, Part)>,
+}
+
+#[derive(Debug, Clone)]
+enum Part {
+    Text(String),
+    File {
+        filename: String,
+        content_type: String,
+        contents: Bytes,
+    },
+    Blob {
+        blob: web_sys::Blob,
+        filename: Option<String>,
+    },
+}
+
+impl FormData {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn text(&mut self, name: impl Into<String>, value: impl Into<String>) -> &mut Self {
+        self.parts.push((name.into(), Part::Text(value.into())));
+        self
+    }
+
+    /// Add a file made from `contents`.
+    pub fn file(
+        &mut self,
+        name: impl Into<String>,
+        filename: impl Into<String>,
+        content_type: impl Into<String>,
+        contents: impl Into<Bytes>,
+    ) -> &mut Self {
+        let part = Part::File {
+            filename: filename.into(),
+            content_type: content_type.into(),
+            contents: contents.into(),
+        };
+        self.parts.push((name.into(), part));
+        self
+    }
+
+    /// Add a blob, or a file from an `<input type="file">`. Files keep their own name unless
+    /// `filename` is given.
+    pub fn blob(
+        &mut self,
+        name: impl Into<String>,
+        blob: web_sys::Blob,
+        filename: Option<&str>,
+    ) -> &mut Self {
+        let part = Part::Blob {
+            blob,
+            filename: filename.map(str::to_owned),
+        };
+        self.parts.push((name.into(), part));
+        self
+    }
+
+    fn to_web_sys(&self) -> Result<web_sys::FormData, DomException> {
+        let form = web_sys::FormData::new().map_err(DomException::from_js)?;
+        for (name, part) in &self.parts {
+            match part {
+                Part::Text(value) => form.append_with_str(name, value),
+                Part::File {
+                    filename,
+                    content_type,
+                    contents,
+                } => {
+                    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(&contents[..]));
+                    let mut options = web_sys::BlobPropertyBag::new();
+                    options.type_(content_type);
+                    let blob =
+                        web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &options)
+                            .map_err(DomException::from_js)?;
+                    form.append_with_blob_and_filename(name, &blob, filename)
+                }
+                Part::Blob {
+                    blob,
+                    filename: Some(filename),
+                } => form.append_with_blob_and_filename(name, blob, filename),
+                Part::Blob {
+                    blob,
+                    filename: None,
+                } => form.append_with_blob(name, blob),
+            }
+            .map_err(DomException::from_js)?;
+        }
+        Ok(form)
+    }
+}
+
+/// A url-encoded form, like the query part of a url.
+///
+/// `to_string` gives the `application/x-www-form-urlencoded` serialization.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct UrlSearchParams {
+    pairs: Vec<(String, String)>,
+}
+
+impl UrlSearchParams {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Add a pair, keeping any existing pairs with the same name.
+    pub fn append(&mut self, name: impl Into<String>, value: impl Into<String>) -> &mut Self {
+        self.pairs.push((name.into(), value.into()));
+        self
+    }
+
+    /// The first value for `name`.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.pairs
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.pairs
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+}
+
+impl<K, V> std::iter::FromIterator<(K, V)> for UrlSearchParams
+where
+    K: Into<String>,
+    V: Into<String>,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        UrlSearchParams {
+            pairs: iter
+                .into_iter()
+                .map(|(name, value)| (name.into(), value.into()))
+                .collect(),
+        }
+    }
+}
+
+fn urlencode_into(input: &str, out: &mut String) {
+    for &b in input.as_bytes() {
+        match b {
+            b'*' | b'-' | b'.' | b'_' | b'0'..=b'9' | b'A'..=b'Z' | b'a'..=b'z' => {
+                out.push(char::from(b))
+            }
+            b' ' => out.push('+'),
+            b => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+}
+
+impl std::fmt::Display for UrlSearchParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut out = String::new();
+        for (idx, (name, value)) in self.pairs.iter().enumerate() {
+            if idx > 0 {
+                out.push('&');
+            }
+            urlencode_into(name, &mut out);
+            out.push('=');
+            urlencode_into(value, &mut out);
+        }
+        f.write_str(&out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn url_search_params() {
+        let params: UrlSearchParams = vec![("q", "rust & wasm"), ("page", "2"), ("emoji", "é")]
+            .into_iter()
+            .collect();
+        assert_eq!(params.to_string(), "q=rust+%26+wasm&page=2&emoji=%C3%A9");
+        assert_eq!(params.get("page"), Some("2"));
+        assert_eq!(
+            Body::from(params).content_type().unwrap(),
+            "application/x-www-form-urlencoded;charset=UTF-8"
+        );
+        assert_eq!(
+            Body::from(vec![1, 2, 3]).content_type().unwrap(),
+            "application/octet-stream"
+        );
+        assert!(Body::from(FormData::new()).content_type().is_none());
+    }
+}
@@ -0,0 +1,384 @@
+//! A client that runs every request through a stack of layers, for things like auth headers and
+//! retries that would otherwise be repeated for each request.
+//!
+//! Each layer is a `Transport` that wraps another `Transport`, so a layer can be tested on its own
+//! by wrapping a `MockTransport`.
+
+use futures::future::{FutureExt, LocalBoxFuture};
+use http::{StatusCode, Uri};
+use std::{fmt, rc::Rc, time::Duration};
+
+use super::{transport::clone_request, Body, FetchError, FetchTransport, Response, Transport};
+use crate::{
+    error::{DomException, DomExceptionKind},
+    time,
+};
+
+/// Sends requests through its layers, and then its transport.
+///
+/// ```rust,no_run
+/// use dommer::fetch::Client;
+/// use http::header::{HeaderValue, AUTHORIZATION};
+///
+/// let client = Client::new()
+///     .base_url("https://api.example.com/v1")
+///     .retry(vec![http::StatusCode::SERVICE_UNAVAILABLE])
+///     .map_request(|request| {
+///         let token = HeaderValue::from_static("Bearer secret");
+///         request.headers_mut().insert(AUTHORIZATION, token);
+///     });
+/// ```
+#[derive(Clone)]
+pub struct Client {
+    transport: Rc<dyn Transport>,
+}
+
+impl Client {
+    /// A client that sends requests with the browser's `fetch`.
+    pub fn new() -> Self {
+        Client::with_transport(FetchTransport)
+    }
+
+    pub fn with_transport(transport: impl Transport + 'static) -> Self {
+        Client {
+            transport: Rc::new(transport),
+        }
+    }
+
+    /// Wrap the client in another layer.
+    ///
+    /// Layers added later are further out, so they see requests first and responses last.
+    pub fn layer<L>(self, layer: impl FnOnce(Rc<dyn Transport>) -> L) -> Self
+    where
+        L: Transport + 'static,
+    {
+        Client::with_transport(layer(self.transport))
+    }
+
+    /// Change every request, e.g. to add a header.
+    pub fn map_request(self, f: impl Fn(&mut http::Request<Body>) + 'static) -> Self {
+        self.layer(|inner| MapRequest::new(inner, f))
+    }
+
+    /// Look at every response, e.g. for logging.
+    pub fn inspect_response(self, f: impl Fn(&Response) + 'static) -> Self {
+        self.layer(|inner| InspectResponse::new(inner, f))
+    }
+
+    /// Retry requests that get one of `statuses`, with the default backoff. Use `layer` with
+    /// `Retry` to change the backoff.
+    pub fn retry(self, statuses: impl IntoIterator<Item = StatusCode>) -> Self {
+        self.layer(|inner| Retry::new(inner, statuses))
+    }
+
+    /// Join relative request urls onto `base`.
+    pub fn base_url(self, base: impl Into<String>) -> Self {
+        self.layer(|inner| BaseUrl::new(inner, base))
+    }
+
+    pub fn send(
+        &self,
+        request: http::Request<Body>,
+    ) -> LocalBoxFuture<'_, Result<Response, FetchError>> {
+        self.transport.send(request)
+    }
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Client::new()
+    }
+}
+
+impl fmt::Debug for Client {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Client")
+    }
+}
+
+impl Transport for Client {
+    fn send(
+        &self,
+        request: http::Request<Body>,
+    ) -> LocalBoxFuture<'_, Result<Response, FetchError>> {
+        self.transport.send(request)
+    }
+}
+
+/// A layer that changes each request before passing it on.
+pub struct MapRequest<T, F> {
+    inner: T,
+    f: F,
+}
+
+impl<T, F> MapRequest<T, F>
+where
+    T: Transport,
+    F: Fn(&mut http::Request<Body>),
+{
+    pub fn new(inner: T, f: F) -> Self {
+        MapRequest { inner, f }
+    }
+}
+
+impl<T, F> Transport for MapRequest<T, F>
+where
+    T: Transport,
+    F: Fn(&mut http::Request<Body>),
+{
+    fn send(
+        &self,
+        mut request: http::Request<Body>,
+    ) -> LocalBoxFuture<'_, Result<Response, FetchError>> {
+        (self.f)(&mut request);
+        self.inner.send(request)
+    }
+}
+
+/// A layer that calls a function with each response, before passing it on.
+pub struct InspectResponse<T, F> {
+    inner: T,
+    f: F,
+}
+
+impl<T, F> InspectResponse<T, F>
+where
+    T: Transport,
+    F: Fn(&Response),
+{
+    pub fn new(inner: T, f: F) -> Self {
+        InspectResponse { inner, f }
+    }
+}
+
+impl<T, F> Transport for InspectResponse<T, F>
+where
+    T: Transport,
+    F: Fn(&Response),
+{
+    fn send(
+        &self,
+        request: http::Request<Body>,
+    ) -> LocalBoxFuture<'_, Result<Response, FetchError>> {
+        async move {
+            let response = self.inner.send(request).await?;
+            (self.f)(&response);
+            Ok(response)
+        }
+        .boxed_local()
+    }
+}
+
+/// A layer that sends a request again when the response has one of the given statuses, waiting
+/// twice as long before each retry.
+///
+/// The response to the last try is returned, whatever its status.
+pub struct Retry<T> {
+    inner: T,
+    statuses: Vec<StatusCode>,
+    max_retries: u32,
+    initial_delay: Duration,
+}
+
+impl<T> Retry<T>
+where
+    T: Transport,
+{
+    /// Retry up to 3 times, waiting 100ms before the first retry.
+    pub fn new(inner: T, statuses: impl IntoIterator<Item = StatusCode>) -> Self {
+        Retry {
+            inner,
+            statuses: statuses.into_iter().collect(),
+            max_retries: 3,
+            initial_delay: Duration::from_millis(100),
+        }
+    }
+
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// How long to wait before the first retry. A zero delay retries straight away, every time.
+    pub fn initial_delay(mut self, initial_delay: Duration) -> Self {
+        self.initial_delay = initial_delay;
+        self
+    }
+
+    fn should_retry(&self, response: &Response) -> bool {
+        self.statuses
+            .iter()
+            .any(|status| status.as_u16() == response.status())
+    }
+}
+
+impl<T> Transport for Retry<T>
+where
+    T: Transport,
+{
+    fn send(
+        &self,
+        request: http::Request<Body>,
+    ) -> LocalBoxFuture<'_, Result<Response, FetchError>> {
+        async move {
+            let mut delay = self.initial_delay;
+            let mut retries = 0;
+            loop {
+                let response = self.inner.send(clone_request(&request)).await?;
+                if retries == self.max_retries || !self.should_retry(&response) {
+                    return Ok(response);
+                }
+                retries += 1;
+                // No timer is needed for no delay, which lets this run outside a browser.
+                if delay > Duration::from_secs(0) {
+                    time::sleep(delay).await;
+                }
+                delay *= 2;
+            }
+        }
+        .boxed_local()
+    }
+}
+
+/// A layer that joins relative request urls onto a base url. Urls with a scheme are left alone.
+///
+/// The path is appended to the base, so with a base of `https://example.com/api`, `/users`
+/// becomes `https://example.com/api/users`.
+pub struct BaseUrl<T> {
+    inner: T,
+    base: String,
+}
+
+impl<T> BaseUrl<T>
+where
+    T: Transport,
+{
+    pub fn new(inner: T, base: impl Into<String>) -> Self {
+        let mut base = base.into();
+        while base.ends_with('/') {
+            base.pop();
+        }
+        BaseUrl { inner, base }
+    }
+
+    fn join(&self, uri: &Uri) -> Result<Uri, DomException> {
+        if uri.scheme_part().is_some() {
+            return Ok(uri.clone());
+        }
+        let joined = format!("{}/{}", self.base, uri.to_string().trim_start_matches('/'));
+        joined.parse().map_err(|_| {
+            DomException::new(
+                DomExceptionKind::Other("TypeError".to_owned()),
+                format!("'{}' is not a valid url", joined),
+            )
+        })
+    }
+}
+
+impl<T> Transport for BaseUrl<T>
+where
+    T: Transport,
+{
+    fn send(
+        &self,
+        mut request: http::Request<Body>,
+    ) -> LocalBoxFuture<'_, Result<Response, FetchError>> {
+        match self.join(request.uri()) {
+            Ok(uri) => {
+                *request.uri_mut() = uri;
+                self.inner.send(request)
+            }
+            Err(e) => futures::future::err(e.into()).boxed_local(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fetch::MockTransport;
+    use futures::executor::block_on;
+    use http::{
+        header::{HeaderValue, AUTHORIZATION},
+        Method,
+    };
+    use std::cell::Cell;
+
+    /// A mock that answers with each of `statuses` in turn.
+    fn mock(statuses: &[u16]) -> MockTransport {
+        let mock = MockTransport::new();
+        for status in statuses {
+            mock.route(Method::GET, "*").once().respond(*status, "");
+        }
+        mock
+    }
+
+    fn get(uri: &str) -> http::Request<Body> {
+        http::Request::get(uri).body(Body::from("")).unwrap()
+    }
+
+    #[test]
+    fn map_request() {
+        let mock = mock(&[200]);
+        let layer = MapRequest::new(mock.clone(), |request: &mut http::Request<Body>| {
+            let token = HeaderValue::from_static("Bearer secret");
+            request.headers_mut().insert(AUTHORIZATION, token);
+        });
+        block_on(layer.send(get("/users"))).unwrap();
+        assert_eq!(mock.requests()[0].headers()[AUTHORIZATION], "Bearer secret");
+    }
+
+    #[test]
+    fn inspect_response() {
+        let seen = Rc::new(Cell::new(0));
+        let layer = InspectResponse::new(mock(&[404]), {
+            let seen = seen.clone();
+            move |response: &Response| seen.set(response.status())
+        });
+        block_on(layer.send(get("/users"))).unwrap();
+        assert_eq!(seen.get(), 404);
+    }
+
+    #[test]
+    fn retry() {
+        let mock = mock(&[503, 503, 200, 503]);
+        let layer = Retry::new(mock.clone(), vec![StatusCode::SERVICE_UNAVAILABLE])
+            .initial_delay(Duration::from_secs(0));
+        let response = block_on(layer.send(get("/users"))).unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(mock.requests().len(), 3);
+
+        let mock = self::mock(&[503, 503, 503]);
+        let layer = Retry::new(mock.clone(), vec![StatusCode::SERVICE_UNAVAILABLE])
+            .initial_delay(Duration::from_secs(0))
+            .max_retries(2);
+        let response = block_on(layer.send(get("/users"))).unwrap();
+        assert_eq!(response.status(), 503);
+        assert_eq!(mock.requests().len(), 3);
+    }
+
+    #[test]
+    fn base_url() {
+        let mock = mock(&[200, 200]);
+        let layer = BaseUrl::new(mock.clone(), "https://example.com/api/");
+        block_on(layer.send(get("/users?page=2"))).unwrap();
+        block_on(layer.send(get("https://other.example.com/"))).unwrap();
+        let requests = mock.requests();
+        assert_eq!(requests[0].uri(), "https://example.com/api/users?page=2");
+        assert_eq!(requests[1].uri(), "https://other.example.com/");
+    }
+
+    #[test]
+    fn client() {
+        let mock = mock(&[500, 200]);
+        let client = Client::with_transport(mock.clone())
+            .layer(|inner| {
+                Retry::new(inner, vec![StatusCode::INTERNAL_SERVER_ERROR])
+                    .initial_delay(Duration::from_secs(0))
+            })
+            .base_url("https://example.com");
+        let response = block_on(client.send(get("/"))).unwrap();
+        assert!(response.ok());
+        assert_eq!(mock.requests()[1].uri(), "https://example.com/");
+    }
+}
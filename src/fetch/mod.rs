@@ -0,0 +1,880 @@
+//! Make http requests using the browser's `fetch` api.
+
+use bytes::Bytes;
+use http::{
+    header::{HeaderName, HeaderValue, CONTENT_LENGTH, CONTENT_TYPE},
+    HeaderMap, Method, StatusCode,
+};
+use std::{
+    cell::{Cell, RefCell},
+    convert::TryFrom,
+    error::Error,
+    fmt,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+    time::Duration,
+};
+use wasm_bindgen::{prelude::*, JsCast};
+use wasm_bindgen_futures::JsFuture;
+
+use crate::{
+    abort::{AbortHandle, AbortSignal},
+    error::{DomException, DomExceptionKind},
+    event::SubscribeGuard,
+    time::Timeout,
+};
+
+mod body;
+pub mod client;
+mod stream;
+mod transport;
+
+pub use self::{
+    body::{Body, FormData, UrlSearchParams},
+    client::Client,
+    stream::{BodyStream, Progress},
+    transport::{FetchTransport, MockRoute, MockTransport, Transport},
+};
+
+#[wasm_bindgen]
+extern "C" {
+    // The global `fetch`, which exists on both windows and workers.
+    #[wasm_bindgen(js_name = fetch)]
+    fn fetch_with_request(input: &web_sys::Request) -> js_sys::Promise;
+}
+
+/// Send `request`, resolving to the response once its headers have arrived.
+///
+/// The future fails if there is a network error, or the request is blocked (e.g. by CORS). Http
+/// error statuses like 404 are not errors, check `Response::ok` for those.
+///
+/// Dropping the future before it resolves aborts the request, and so does dropping the
+/// `Response` before its body has been read.
+pub fn fetch(request: &Request) -> ResponseFuture {
+    let (handle, signal) = AbortHandle::new();
+    let follow = request
+        .signal
+        .as_ref()
+        .and_then(|signal| handle.follow(signal));
+    let mut init = web_sys::RequestInit::new();
+    init.signal(Some(signal.as_web_sys()));
+    let promise = match web_sys::Request::new_with_request_and_init(&request.inner, &init) {
+        Ok(request) => fetch_with_request(&request),
+        Err(e) => js_sys::Promise::reject(&e),
+    };
+    ResponseFuture {
+        inner: JsFuture::from(promise),
+        in_flight: Some(InFlight {
+            handle,
+            _follow: follow,
+        }),
+        timeout: None,
+        timed_out: Rc::new(Cell::new(false)),
+    }
+}
+
+/// The future returned by `fetch`.
+#[derive(Debug)]
+pub struct ResponseFuture {
+    inner: JsFuture,
+    in_flight: Option<InFlight>,
+    timeout: Option<Timeout>,
+    timed_out: Rc<Cell<bool>>,
+}
+
+/// Keeps a request alive, aborting it when dropped.
+#[derive(Debug)]
+struct InFlight {
+    handle: AbortHandle,
+    // Aborts `handle` when the signal passed to `RequestBuilder::signal` is aborted.
+    _follow: Option<SubscribeGuard>,
+}
+
+impl ResponseFuture {
+    /// Abort the request if the response hasn't arrived after `timeout`, failing with
+    /// `FetchError::TimedOut`.
+    ///
+    /// This only covers waiting for the headers, not reading the body.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        let abort = match &self.in_flight {
+            Some(in_flight) => in_flight.handle.abort_fn(),
+            None => return self,
+        };
+        let timed_out = self.timed_out.clone();
+        self.timeout = Some(Timeout::new(timeout, move || {
+            timed_out.set(true);
+            abort();
+        }));
+        self
+    }
+}
+
+impl Future for ResponseFuture {
+    type Output = Result<Response, FetchError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let res = match Pin::new(&mut self.inner).poll(cx) {
+            Poll::Ready(res) => res,
+            Poll::Pending => return Poll::Pending,
+        };
+        self.timeout = None;
+        Poll::Ready(match res {
+            Ok(inner) => Ok(Response {
+                inner: Inner::Web(inner.unchecked_into()),
+                _in_flight: self.in_flight.take(),
+            }),
+            Err(e) => match FetchError::from_js(e) {
+                FetchError::Aborted if self.timed_out.get() => Err(FetchError::TimedOut),
+                err => Err(err),
+            },
+        })
+    }
+}
+
+/// The ways a request, or reading its response, can fail.
+#[derive(Debug)]
+pub enum FetchError {
+    /// The browser rejected the request or the body, e.g. a network error, a CORS failure, or
+    /// reading a body that has already been read.
+    Dom(DomException),
+    /// The request was aborted through its `AbortSignal`.
+    Aborted,
+    /// The request was aborted because it took longer than `ResponseFuture::timeout`.
+    TimedOut,
+    /// The body wasn't valid json for the type it was read as.
+    #[cfg(feature = "serde")]
+    Json(serde_json::Error),
+}
+
+impl FetchError {
+    fn from_js(err: JsValue) -> Self {
+        let err = DomException::from_js(err);
+        if *err.kind() == DomExceptionKind::Abort {
+            FetchError::Aborted
+        } else {
+            FetchError::Dom(err)
+        }
+    }
+}
+
+impl From<DomException> for FetchError {
+    fn from(err: DomException) -> Self {
+        FetchError::Dom(err)
+    }
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FetchError::Dom(err) => fmt::Display::fmt(err, f),
+            FetchError::Aborted => f.write_str("the request was aborted"),
+            FetchError::TimedOut => f.write_str("the request timed out"),
+            #[cfg(feature = "serde")]
+            FetchError::Json(err) => write!(f, "could not parse the body: {}", err),
+        }
+    }
+}
+
+impl Error for FetchError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FetchError::Dom(err) => Some(err),
+            FetchError::Aborted | FetchError::TimedOut => None,
+            #[cfg(feature = "serde")]
+            FetchError::Json(err) => Some(err),
+        }
+    }
+}
+
+/// The response to a request.
+///
+/// Usually this comes from the browser, but responses can also be made from an `http::Response`,
+/// for example to return canned responses from a test `Transport`.
+///
+/// The body can only be read once, reading it again fails with a `TypeError`.
+#[derive(Debug)]
+pub struct Response {
+    inner: Inner,
+    _in_flight: Option<InFlight>,
+}
+
+#[derive(Debug)]
+enum Inner {
+    Web(web_sys::Response),
+    Http {
+        parts: http::response::Parts,
+        body: RefCell<Option<Bytes>>,
+    },
+}
+
+impl Response {
+    pub fn status(&self) -> u16 {
+        match &self.inner {
+            Inner::Web(inner) => inner.status(),
+            Inner::Http { parts, .. } => parts.status.as_u16(),
+        }
+    }
+
+    pub fn status_text(&self) -> String {
+        match &self.inner {
+            Inner::Web(inner) => inner.status_text(),
+            Inner::Http { parts, .. } => parts
+                .status
+                .canonical_reason()
+                .unwrap_or_default()
+                .to_owned(),
+        }
+    }
+
+    /// Whether the status is in the range 200-299.
+    pub fn ok(&self) -> bool {
+        (200..300).contains(&self.status())
+    }
+
+    /// The headers of the response. Headers the browser hides from scripts (e.g. because of
+    /// CORS) are not included.
+    pub fn headers(&self) -> HeaderMap {
+        match &self.inner {
+            Inner::Web(inner) => header_map_from_web_sys(&inner.headers()),
+            Inner::Http { parts, .. } => parts.headers.clone(),
+        }
+    }
+
+    /// The final url of the response, after any redirects. Empty for responses made from an
+    /// `http::Response`.
+    pub fn url(&self) -> String {
+        match &self.inner {
+            Inner::Web(inner) => inner.url(),
+            Inner::Http { .. } => String::new(),
+        }
+    }
+
+    pub fn redirected(&self) -> bool {
+        match &self.inner {
+            Inner::Web(inner) => inner.redirected(),
+            Inner::Http { .. } => false,
+        }
+    }
+
+    pub fn body_used(&self) -> bool {
+        match &self.inner {
+            Inner::Web(inner) => inner.body_used(),
+            Inner::Http { body, .. } => body.borrow().is_none(),
+        }
+    }
+
+    /// The error from reading a body a second time.
+    fn body_used_error() -> FetchError {
+        FetchError::Dom(DomException::new(
+            DomExceptionKind::Other("TypeError".to_owned()),
+            "the body has already been read",
+        ))
+    }
+
+    /// Take the body of a response made from an `http::Response`.
+    fn take_body(body: &RefCell<Option<Bytes>>) -> Result<Bytes, FetchError> {
+        body.borrow_mut()
+            .take()
+            .ok_or_else(Response::body_used_error)
+    }
+
+    /// Read the body as utf-8 text.
+    pub async fn text(&self) -> Result<String, FetchError> {
+        let inner = match &self.inner {
+            Inner::Web(inner) => inner,
+            Inner::Http { body, .. } => {
+                let body = Response::take_body(body)?;
+                return Ok(String::from_utf8_lossy(&body).into_owned());
+            }
+        };
+        let promise = inner.text().map_err(FetchError::from_js)?;
+        let text = JsFuture::from(promise).await.map_err(FetchError::from_js)?;
+        // `text` always resolves to a string.
+        Ok(text.as_string().unwrap_or_default())
+    }
+
+    /// Read the body as raw bytes.
+    pub async fn bytes(&self) -> Result<Bytes, FetchError> {
+        let inner = match &self.inner {
+            Inner::Web(inner) => inner,
+            Inner::Http { body, .. } => return Response::take_body(body),
+        };
+        let promise = inner.array_buffer().map_err(FetchError::from_js)?;
+        let buffer = JsFuture::from(promise).await.map_err(FetchError::from_js)?;
+        Ok(js_sys::Uint8Array::new(&buffer).to_vec().into())
+    }
+
+    /// Read the body as json, and deserialize it into a `T`.
+    #[cfg(feature = "serde")]
+    pub async fn json<T>(&self) -> Result<T, FetchError>
+    where
+        T: serde_crate::de::DeserializeOwned,
+    {
+        let text = self.text().await?;
+        serde_json::from_str(&text).map_err(FetchError::Json)
+    }
+
+    /// Read the body in chunks as they arrive, rather than waiting for all of it.
+    ///
+    /// `BodyStream::progress` uses the `Content-Length` header to say how far through the body
+    /// the stream is.
+    ///
+    /// If the body has already been read, the stream gives a `TypeError` and then ends.
+    pub fn body_stream(self) -> BodyStream {
+        let total = self
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|len| len.to_str().ok())
+            .and_then(|len| len.trim().parse().ok());
+        // The browser throws if we ask for a reader on a body that has been read.
+        if self.body_used() {
+            return BodyStream::from_error(Response::body_used_error(), total);
+        }
+        match self.inner {
+            Inner::Web(inner) => BodyStream::new(inner.body(), total, self._in_flight),
+            Inner::Http { body, .. } => BodyStream::from_bytes(body.into_inner(), total),
+        }
+    }
+
+    /// Read the body and convert to an `http::Response`, so code written against the `http` types
+    /// can use it.
+    ///
+    /// Fails with a `NotSupportedError` for opaque responses (from `RequestMode::NoCors`), whose
+    /// status is 0.
+    pub async fn into_http(self) -> Result<http::Response<Bytes>, FetchError> {
+        let status = StatusCode::from_u16(self.status()).map_err(|_| {
+            DomException::new(
+                DomExceptionKind::NotSupported,
+                "an opaque response has no status",
+            )
+        })?;
+        let headers = self.headers();
+        let mut response = http::Response::new(self.bytes().await?);
+        *response.status_mut() = status;
+        *response.headers_mut() = headers;
+        Ok(response)
+    }
+
+    /// The underlying `web_sys` response, if this response comes from the browser.
+    pub fn as_web_sys(&self) -> Option<&web_sys::Response> {
+        match &self.inner {
+            Inner::Web(inner) => Some(inner),
+            Inner::Http { .. } => None,
+        }
+    }
+}
+
+impl<B> From<http::Response<B>> for Response
+where
+    B: Into<Bytes>,
+{
+    fn from(response: http::Response<B>) -> Self {
+        let (parts, body) = response.into_parts();
+        Response {
+            inner: Inner::Http {
+                parts,
+                body: RefCell::new(Some(body.into())),
+            },
+            _in_flight: None,
+        }
+    }
+}
+
+/// Copy the entries of a javascript `Headers` object. The browser validates names and values, so
+/// none should be skipped in practice.
+fn header_map_from_web_sys(headers: &web_sys::Headers) -> HeaderMap {
+    let mut map = HeaderMap::new();
+    let entries = match js_sys::try_iter(headers) {
+        Ok(Some(entries)) => entries,
+        _ => return map,
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let entry: js_sys::Array = entry.unchecked_into();
+        let (name, value) = match (entry.get(0).as_string(), entry.get(1).as_string()) {
+            (Some(name), Some(value)) => (name, value),
+            _ => continue,
+        };
+        // Header values are javascript `ByteString`s, where each char is one byte.
+        let value: Vec<u8> = value.chars().map(|c| c as u8).collect();
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_bytes(&value),
+        ) {
+            map.append(name, value);
+        }
+    }
+    map
+}
+
+/// Build a javascript `Headers` object, keeping repeated headers.
+///
+/// Fails with a `TypeError` if a header is forbidden for the headers' guard, which can only
+/// happen for headers of a request that already exists.
+fn header_map_to_web_sys(map: &HeaderMap) -> Result<web_sys::Headers, DomException> {
+    let headers = web_sys::Headers::new().map_err(DomException::from_js)?;
+    for (name, value) in map {
+        let value: String = value.as_bytes().iter().map(|&b| char::from(b)).collect();
+        headers
+            .append(name.as_str(), &value)
+            .map_err(DomException::from_js)?;
+    }
+    Ok(headers)
+}
+
+#[derive(Debug)]
+pub struct Request {
+    inner: web_sys::Request,
+    signal: Option<AbortSignal>,
+}
+
+impl Request {
+    pub fn new() -> RequestBuilder {
+        Default::default()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct RequestBuilder {
+    body: Option<Body>,
+    // The fetch options are only passed on when they were chosen, so the browser's defaults
+    // apply otherwise.
+    cache: Option<RequestCache>,
+    credentials: Option<RequestCredentials>,
+    headers: HeaderMap,
+    integrity: Option<String>,
+    method: Method,
+    mode: Option<RequestMode>,
+    //observe,
+    redirect: Option<RequestRedirect>,
+    referrer: Option<String>,
+    referrer_policy: Option<ReferrerPolicy>,
+    signal: Option<AbortSignal>,
+}
+
+impl RequestBuilder {
+    /// The body to send, e.g. a `String`, a `Vec<u8>` or a `FormData`. Also sets the
+    /// `Content-Type` header, if it isn't set already.
+    pub fn body(&mut self, body: impl Into<Body>) -> &mut Self {
+        self.body = Some(body.into());
+        self
+    }
+
+    pub fn cache(&mut self, cache: RequestCache) -> &mut Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    pub fn credentials(&mut self, credentials: RequestCredentials) -> &mut Self {
+        self.credentials = Some(credentials);
+        self
+    }
+
+    /// Add a header, keeping any existing headers with the same name.
+    pub fn header(&mut self, name: HeaderName, value: HeaderValue) -> &mut Self {
+        self.headers.append(name, value);
+        self
+    }
+
+    /// Replace all the headers.
+    ///
+    /// The browser silently drops headers that scripts aren't allowed to set, like `Host` or
+    /// `Cookie`.
+    pub fn headers(&mut self, headers: HeaderMap) -> &mut Self {
+        self.headers = headers;
+        self
+    }
+
+    pub fn headers_mut(&mut self) -> &mut HeaderMap {
+        &mut self.headers
+    }
+
+    /// The subresource integrity metadata of the response, e.g. `sha256-<base64 hash>`.
+    pub fn integrity(&mut self, integrity: impl Into<String>) -> &mut Self {
+        self.integrity = Some(integrity.into());
+        self
+    }
+
+    pub fn method(&mut self, method: Method) -> &mut Self {
+        self.method = method;
+        self
+    }
+
+    pub fn mode(&mut self, mode: RequestMode) -> &mut Self {
+        self.mode = Some(mode);
+        self
+    }
+
+    pub fn redirect(&mut self, redirect: RequestRedirect) -> &mut Self {
+        self.redirect = Some(redirect);
+        self
+    }
+
+    /// A same-origin url, or the empty string to send no referrer. Defaults to the url of the
+    /// current page.
+    pub fn referrer(&mut self, referrer: impl Into<String>) -> &mut Self {
+        self.referrer = Some(referrer.into());
+        self
+    }
+
+    pub fn referrer_policy(&mut self, referrer_policy: ReferrerPolicy) -> &mut Self {
+        self.referrer_policy = Some(referrer_policy);
+        self
+    }
+
+    /// Abort the request, or the reading of its body, when `signal` is aborted.
+    pub fn signal(&mut self, signal: AbortSignal) -> &mut Self {
+        self.signal = Some(signal);
+        self
+    }
+
+    /// Fails with a `TypeError` if `url` cannot be parsed, or contains credentials, or the
+    /// options can't be used together (see `validate`).
+    pub fn build(&self, url: &str) -> Result<Request, DomException> {
+        self.validate()?;
+        let mut headers = self.headers.clone();
+        if let Some(content_type) = self.body.as_ref().and_then(Body::content_type) {
+            if !headers.contains_key(CONTENT_TYPE) {
+                headers.insert(CONTENT_TYPE, content_type);
+            }
+        }
+        let headers = header_map_to_web_sys(&headers)?;
+        let mut init = web_sys::RequestInit::new();
+        init.method(self.method.as_str()).headers(&headers);
+        if let Some(cache) = self.cache {
+            init.cache(cache.into());
+        }
+        if let Some(credentials) = self.credentials {
+            init.credentials(credentials.into());
+        }
+        if let Some(mode) = self.mode {
+            init.mode(mode.into());
+        }
+        if let Some(redirect) = self.redirect {
+            init.redirect(redirect.into());
+        }
+        if let Some(referrer_policy) = self.referrer_policy {
+            init.referrer_policy(referrer_policy.into());
+        }
+        if let Some(body) = &self.body {
+            init.body(Some(&body.to_js()?));
+        }
+        if let Some(integrity) = &self.integrity {
+            init.integrity(integrity);
+        }
+        if let Some(referrer) = &self.referrer {
+            init.referrer(referrer);
+        }
+        if let Some(signal) = &self.signal {
+            init.signal(Some(signal.as_web_sys()));
+        }
+        let inner =
+            web_sys::Request::new_with_str_and_init(url, &init).map_err(DomException::from_js)?;
+        Ok(Request {
+            inner,
+            signal: self.signal.clone(),
+        })
+    }
+
+    /// Check the options that the browser would reject together, without making a request.
+    ///
+    /// Fails with a `TypeError` if
+    ///  - the method is `GET` or `HEAD` and there is a body,
+    ///  - the cache mode is `OnlyIfCached` and the mode isn't `SameOrigin`, or
+    ///  - the mode is `Navigate`, which only the browser can use.
+    pub fn validate(&self) -> Result<(), DomException> {
+        let message =
+            if self.body.is_some() && (self.method == Method::GET || self.method == Method::HEAD) {
+                "a request with a GET or HEAD method cannot have a body"
+            } else if self.cache == Some(RequestCache::OnlyIfCached)
+                && self.mode != Some(RequestMode::SameOrigin)
+            {
+                "the 'only-if-cached' cache mode can only be used with the 'same-origin' mode"
+            } else if self.mode == Some(RequestMode::Navigate) {
+                "a request cannot be constructed with the 'navigate' mode"
+            } else {
+                return Ok(());
+            };
+        Err(DomException::new(
+            DomExceptionKind::Other("TypeError".to_owned()),
+            message,
+        ))
+    }
+}
+
+impl Request {
+    pub fn method(&self) -> Method {
+        // The browser normalizes the method, so it is always valid.
+        Method::from_bytes(self.inner.method().as_bytes()).unwrap_or_default()
+    }
+
+    pub fn url(&self) -> String {
+        self.inner.url()
+    }
+
+    pub fn credentials(&self) -> RequestCredentials {
+        self.inner.credentials().into()
+    }
+
+    /// The headers of the request, without any the browser dropped because they are forbidden.
+    pub fn headers(&self) -> HeaderMap {
+        header_map_from_web_sys(&self.inner.headers())
+    }
+
+    /// Fails with a `TypeError` if the body has already been read.
+    pub fn try_clone(&self) -> Result<Request, DomException> {
+        let inner = self.inner.clone().map_err(DomException::from_js)?;
+        Ok(Request {
+            inner,
+            signal: self.signal.clone(),
+        })
+    }
+}
+
+/// Converts the method, uri, headers and body. An empty body is treated as no body, so `GET`
+/// requests can be converted.
+///
+/// The fetch options (`RequestCache`, `RequestCredentials`, `RequestMode`, `RequestRedirect` and
+/// `ReferrerPolicy`) are taken from the request's extensions, if they are there.
+///
+/// Fails for the same reasons as `RequestBuilder::build`.
+impl<B> TryFrom<http::Request<B>> for Request
+where
+    B: Into<Body>,
+{
+    type Error = DomException;
+
+    fn try_from(request: http::Request<B>) -> Result<Self, Self::Error> {
+        let (parts, body) = request.into_parts();
+        let body = body.into();
+        let mut builder = Request::new();
+        builder.method(parts.method).headers(parts.headers);
+        if !body.is_empty() {
+            builder.body(body);
+        }
+        let extensions = &parts.extensions;
+        if let Some(cache) = extensions.get::<RequestCache>() {
+            builder.cache(*cache);
+        }
+        if let Some(credentials) = extensions.get::<RequestCredentials>() {
+            builder.credentials(*credentials);
+        }
+        if let Some(mode) = extensions.get::<RequestMode>() {
+            builder.mode(*mode);
+        }
+        if let Some(redirect) = extensions.get::<RequestRedirect>() {
+            builder.redirect(*redirect);
+        }
+        if let Some(referrer_policy) = extensions.get::<ReferrerPolicy>() {
+            builder.referrer_policy(*referrer_policy);
+        }
+        builder.build(&parts.uri.to_string())
+    }
+}
+
+impl Clone for Request {
+    fn clone(&self) -> Self {
+        expect!(self.try_clone(), "calling Request::clone")
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RequestCache {
+    Default,
+    NoStore,
+    Reload,
+    NoCache,
+    ForceCache,
+    OnlyIfCached,
+}
+
+impl Default for RequestCache {
+    fn default() -> Self {
+        RequestCache::Default
+    }
+}
+
+impl From<RequestCache> for web_sys::RequestCache {
+    fn from(x: RequestCache) -> Self {
+        match x {
+            RequestCache::Default => web_sys::RequestCache::Default,
+            RequestCache::NoStore => web_sys::RequestCache::NoStore,
+            RequestCache::Reload => web_sys::RequestCache::Reload,
+            RequestCache::NoCache => web_sys::RequestCache::NoCache,
+            RequestCache::ForceCache => web_sys::RequestCache::ForceCache,
+            RequestCache::OnlyIfCached => web_sys::RequestCache::OnlyIfCached,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RequestCredentials {
+    Omit,
+    SameOrigin,
+    Include,
+}
+
+/// The same default as `fetch`.
+impl Default for RequestCredentials {
+    fn default() -> Self {
+        RequestCredentials::SameOrigin
+    }
+}
+
+impl From<web_sys::RequestCredentials> for RequestCredentials {
+    fn from(x: web_sys::RequestCredentials) -> Self {
+        match x {
+            web_sys::RequestCredentials::Omit => RequestCredentials::Omit,
+            web_sys::RequestCredentials::Include => RequestCredentials::Include,
+            _ => RequestCredentials::SameOrigin,
+        }
+    }
+}
+
+impl From<RequestCredentials> for web_sys::RequestCredentials {
+    fn from(x: RequestCredentials) -> Self {
+        match x {
+            RequestCredentials::Omit => web_sys::RequestCredentials::Omit,
+            RequestCredentials::SameOrigin => web_sys::RequestCredentials::SameOrigin,
+            RequestCredentials::Include => web_sys::RequestCredentials::Include,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RequestMode {
+    SameOrigin,
+    NoCors,
+    Cors,
+    Navigate,
+}
+
+impl Default for RequestMode {
+    fn default() -> Self {
+        RequestMode::Cors
+    }
+}
+
+impl From<RequestMode> for web_sys::RequestMode {
+    fn from(x: RequestMode) -> Self {
+        match x {
+            RequestMode::SameOrigin => web_sys::RequestMode::SameOrigin,
+            RequestMode::NoCors => web_sys::RequestMode::NoCors,
+            RequestMode::Cors => web_sys::RequestMode::Cors,
+            RequestMode::Navigate => web_sys::RequestMode::Navigate,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RequestRedirect {
+    Follow,
+    Error,
+    Manual,
+}
+
+impl Default for RequestRedirect {
+    fn default() -> Self {
+        RequestRedirect::Follow
+    }
+}
+
+impl From<RequestRedirect> for web_sys::RequestRedirect {
+    fn from(x: RequestRedirect) -> Self {
+        match x {
+            RequestRedirect::Follow => web_sys::RequestRedirect::Follow,
+            RequestRedirect::Error => web_sys::RequestRedirect::Error,
+            RequestRedirect::Manual => web_sys::RequestRedirect::Manual,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ReferrerPolicy {
+    None,
+    NoReferrer,
+    NoReferrerWhenDowngrade,
+    Origin,
+    OriginWhenCrossOrigin,
+    UnsafeUrl,
+    SameOrigin,
+    StrictOrigin,
+    StrictOriginWhenCrossOrigin,
+}
+
+impl Default for ReferrerPolicy {
+    fn default() -> Self {
+        ReferrerPolicy::None
+    }
+}
+
+impl From<ReferrerPolicy> for web_sys::ReferrerPolicy {
+    fn from(x: ReferrerPolicy) -> Self {
+        match x {
+            ReferrerPolicy::None => web_sys::ReferrerPolicy::None,
+            ReferrerPolicy::NoReferrer => web_sys::ReferrerPolicy::NoReferrer,
+            ReferrerPolicy::NoReferrerWhenDowngrade => {
+                web_sys::ReferrerPolicy::NoReferrerWhenDowngrade
+            }
+            ReferrerPolicy::Origin => web_sys::ReferrerPolicy::Origin,
+            ReferrerPolicy::OriginWhenCrossOrigin => web_sys::ReferrerPolicy::OriginWhenCrossOrigin,
+            ReferrerPolicy::UnsafeUrl => web_sys::ReferrerPolicy::UnsafeUrl,
+            ReferrerPolicy::SameOrigin => web_sys::ReferrerPolicy::SameOrigin,
+            ReferrerPolicy::StrictOrigin => web_sys::ReferrerPolicy::StrictOrigin,
+            ReferrerPolicy::StrictOriginWhenCrossOrigin => {
+                web_sys::ReferrerPolicy::StrictOriginWhenCrossOrigin
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate() {
+        assert!(Request::new().validate().is_ok());
+        let err = Request::new().body("hello").validate().unwrap_err();
+        assert_eq!(err.name(), "TypeError");
+        assert!(Request::new()
+            .method(Method::POST)
+            .body("hello")
+            .validate()
+            .is_ok());
+        assert!(Request::new()
+            .cache(RequestCache::OnlyIfCached)
+            .validate()
+            .is_err());
+        assert!(Request::new()
+            .cache(RequestCache::OnlyIfCached)
+            .mode(RequestMode::SameOrigin)
+            .validate()
+            .is_ok());
+    }
+
+    #[test]
+    fn body_stream_after_read() {
+        use futures::{executor::block_on, StreamExt};
+
+        let response = Response::from(http::Response::new("hello"));
+        assert_eq!(block_on(response.text()).unwrap(), "hello");
+        let mut stream = response.body_stream();
+        match block_on(stream.next()) {
+            Some(Err(FetchError::Dom(err))) => assert_eq!(err.name(), "TypeError"),
+            other => panic!("expected a TypeError, got {:?}", other),
+        }
+        assert!(block_on(stream.next()).is_none());
+    }
+
+    #[test]
+    fn default_credentials() {
+        // Unless they are chosen, credentials are left to the browser, which uses the default.
+        assert_eq!(Request::new().credentials, None);
+        assert_eq!(
+            RequestCredentials::default(),
+            RequestCredentials::SameOrigin
+        );
+    }
+}
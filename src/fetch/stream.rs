@@ -0,0 +1,186 @@
+//! Reading a response body as it arrives.
+
+use bytes::Bytes;
+use futures::Stream;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+use super::{FetchError, InFlight};
+
+/// The body of a response, as a stream of chunks in the order they arrive.
+///
+/// Dropping the stream before the end cancels the download.
+#[derive(Debug)]
+pub struct BodyStream {
+    reader: Option<web_sys::ReadableStreamDefaultReader>,
+    // The body of a response that didn't come from the browser.
+    buffered: Option<Bytes>,
+    // Given before anything else, for a body that couldn't be read.
+    error: Option<FetchError>,
+    pending: Option<JsFuture>,
+    progress: Progress,
+    _in_flight: Option<InFlight>,
+}
+
+impl BodyStream {
+    pub(super) fn new(
+        body: Option<web_sys::ReadableStream>,
+        total: Option<u64>,
+        in_flight: Option<InFlight>,
+    ) -> Self {
+        BodyStream {
+            reader: body.map(|body| body.get_reader().unchecked_into()),
+            buffered: None,
+            error: None,
+            pending: None,
+            progress: Progress { received: 0, total },
+            _in_flight: in_flight,
+        }
+    }
+
+    /// A stream of a single chunk, for a response that didn't come from the browser.
+    pub(super) fn from_bytes(body: Option<Bytes>, total: Option<u64>) -> Self {
+        BodyStream {
+            reader: None,
+            buffered: body,
+            error: None,
+            pending: None,
+            progress: Progress { received: 0, total },
+            _in_flight: None,
+        }
+    }
+
+    /// A stream that gives `error` and then ends.
+    pub(super) fn from_error(error: FetchError, total: Option<u64>) -> Self {
+        BodyStream {
+            reader: None,
+            buffered: None,
+            error: Some(error),
+            pending: None,
+            progress: Progress { received: 0, total },
+            _in_flight: None,
+        }
+    }
+
+    /// How much of the body has been received so far.
+    pub fn progress(&self) -> Progress {
+        self.progress
+    }
+
+    /// Get the bytes out of the object `ReadableStreamDefaultReader::read` resolves to, or `None`
+    /// at the end of the stream.
+    fn chunk(result: &JsValue) -> Option<Bytes> {
+        let done = js_sys::Reflect::get(result, &JsValue::from_str("done"))
+            .ok()
+            .and_then(|done| done.as_bool())
+            .unwrap_or(true);
+        if done {
+            return None;
+        }
+        let value = js_sys::Reflect::get(result, &JsValue::from_str("value")).ok()?;
+        Some(js_sys::Uint8Array::new(&value).to_vec().into())
+    }
+}
+
+impl Stream for BodyStream {
+    type Item = Result<Bytes, FetchError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        if let Some(error) = self.error.take() {
+            return Poll::Ready(Some(Err(error)));
+        }
+        if let Some(chunk) = self.buffered.take() {
+            self.progress.received += chunk.len() as u64;
+            return Poll::Ready(Some(Ok(chunk)));
+        }
+        if self.pending.is_none() {
+            let promise = match &self.reader {
+                Some(reader) => reader.read(),
+                None => return Poll::Ready(None),
+            };
+            self.pending = Some(JsFuture::from(promise));
+        }
+        let res = match self
+            .pending
+            .as_mut()
+            .map(|pending| Pin::new(pending).poll(cx))
+        {
+            Some(Poll::Ready(res)) => res,
+            _ => return Poll::Pending,
+        };
+        self.pending = None;
+        let chunk = res.map(|result| BodyStream::chunk(&result));
+        match chunk {
+            Ok(Some(chunk)) => {
+                self.progress.received += chunk.len() as u64;
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            Ok(None) => {
+                self.reader = None;
+                Poll::Ready(None)
+            }
+            Err(e) => {
+                self.reader = None;
+                Poll::Ready(Some(Err(FetchError::from_js(e))))
+            }
+        }
+    }
+}
+
+impl Drop for BodyStream {
+    fn drop(&mut self) {
+        if let Some(reader) = self.reader.take() {
+            // The promise only tells us when the cancel is done.
+            let _ = reader.cancel();
+        }
+    }
+}
+
+/// How much of a body has been downloaded.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Progress {
+    /// The number of bytes received so far.
+    pub received: u64,
+    /// The `Content-Length` of the response, if it had one.
+    ///
+    /// For compressed responses this is the compressed size, while `received` counts the
+    /// decompressed bytes.
+    pub total: Option<u64>,
+}
+
+impl Progress {
+    /// The fraction of the body received, between 0 and 1, if the length is known.
+    pub fn fraction(&self) -> Option<f64> {
+        match self.total {
+            Some(0) => Some(1.),
+            Some(total) => Some((self.received as f64 / total as f64).min(1.)),
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress() {
+        let mut progress = Progress {
+            received: 0,
+            total: Some(200),
+        };
+        assert_eq!(progress.fraction(), Some(0.));
+        progress.received = 50;
+        assert_eq!(progress.fraction(), Some(0.25));
+        // A compressed body can decompress to more than its `Content-Length`.
+        progress.received = 300;
+        assert_eq!(progress.fraction(), Some(1.));
+        progress.total = None;
+        assert_eq!(progress.fraction(), None);
+    }
+}
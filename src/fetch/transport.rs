@@ -0,0 +1,349 @@
+//! The layer of a `Client` that actually sends requests, either over the network or to a
+//! `MockTransport` in tests.
+
+use bytes::Bytes;
+use futures::future::{self, FutureExt, LocalBoxFuture};
+use http::{header::HeaderName, Method};
+use std::{cell::RefCell, convert::TryFrom, fmt, rc::Rc};
+
+use super::{
+    fetch, Body, FetchError, ReferrerPolicy, Request, RequestCache, RequestCredentials,
+    RequestMode, RequestRedirect, Response,
+};
+use crate::error::{DomException, DomExceptionKind};
+
+/// Something that can send a request and get back a response.
+///
+/// `FetchTransport` sends requests over the network, and the layers in `client` wrap another
+/// transport to change what goes in or out.
+pub trait Transport {
+    fn send(
+        &self,
+        request: http::Request<Body>,
+    ) -> LocalBoxFuture<'_, Result<Response, FetchError>>;
+}
+
+impl<T> Transport for Rc<T>
+where
+    T: Transport + ?Sized,
+{
+    fn send(
+        &self,
+        request: http::Request<Body>,
+    ) -> LocalBoxFuture<'_, Result<Response, FetchError>> {
+        (**self).send(request)
+    }
+}
+
+/// Sends requests with the browser's `fetch`.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct FetchTransport;
+
+impl Transport for FetchTransport {
+    fn send(
+        &self,
+        request: http::Request<Body>,
+    ) -> LocalBoxFuture<'_, Result<Response, FetchError>> {
+        match Request::try_from(request) {
+            Ok(request) => fetch(&request).boxed_local(),
+            Err(e) => future::err(e.into()).boxed_local(),
+        }
+    }
+}
+
+/// Copy a request, including the fetch options in its extensions.
+pub(super) fn clone_request(request: &http::Request<Body>) -> http::Request<Body> {
+    fn copy_extension<T>(from: &http::Request<Body>, to: &mut http::Request<Body>)
+    where
+        T: Copy + Send + Sync + 'static,
+    {
+        if let Some(val) = from.extensions().get::<T>() {
+            to.extensions_mut().insert(*val);
+        }
+    }
+
+    let mut clone = http::Request::new(request.body().clone());
+    *clone.method_mut() = request.method().clone();
+    *clone.uri_mut() = request.uri().clone();
+    *clone.version_mut() = request.version();
+    *clone.headers_mut() = request.headers().clone();
+    copy_extension::<RequestCache>(request, &mut clone);
+    copy_extension::<RequestCredentials>(request, &mut clone);
+    copy_extension::<RequestMode>(request, &mut clone);
+    copy_extension::<RequestRedirect>(request, &mut clone);
+    copy_extension::<ReferrerPolicy>(request, &mut clone);
+    clone
+}
+
+/// A transport for tests, that answers requests with canned responses instead of sending them.
+///
+/// Requests are answered by the first route that matches, and every request is recorded so tests
+/// can check what was sent. Requests that match no route fail with a `NotFoundError`.
+///
+/// Clones share their routes and recorded requests, so one clone can be given to a `Client` and
+/// another kept to check on.
+///
+/// ```rust
+/// use dommer::fetch::{Client, MockTransport};
+/// use http::Method;
+///
+/// let mock = MockTransport::new();
+/// mock.route(Method::GET, "/users/*").respond(200, r#"{"name": "Alice"}"#);
+/// let client = Client::with_transport(mock.clone());
+/// ```
+#[derive(Clone, Default)]
+pub struct MockTransport {
+    inner: Rc<MockInner>,
+}
+
+#[derive(Default)]
+struct MockInner {
+    routes: RefCell<Vec<Route>>,
+    requests: RefCell<Vec<http::Request<Body>>>,
+}
+
+struct Route {
+    method: Method,
+    pattern: String,
+    headers: Vec<(HeaderName, String)>,
+    once: bool,
+    respond: Responder,
+}
+
+type Responder = Box<dyn Fn(&http::Request<Body>) -> http::Response<Bytes>>;
+
+impl Route {
+    fn matches(&self, request: &http::Request<Body>) -> bool {
+        let uri = request.uri();
+        // Patterns starting with '/' only look at the path and query.
+        let target = if self.pattern.starts_with('/') {
+            uri.path_and_query()
+                .map_or_else(|| uri.path().to_owned(), |path| path.as_str().to_owned())
+        } else {
+            uri.to_string()
+        };
+        self.method == request.method()
+            && glob_matches(&self.pattern, &target)
+            && self.headers.iter().all(|(name, value)| {
+                request
+                    .headers()
+                    .get_all(name)
+                    .iter()
+                    .any(|actual| actual == value.as_str())
+            })
+    }
+}
+
+/// Match `input` against `pattern`, where `*` in the pattern matches any run of characters.
+fn glob_matches(pattern: &str, input: &str) -> bool {
+    let mut parts = pattern.split('*');
+    // There is always a first part, even if it's empty.
+    let first = parts.next().unwrap_or("");
+    if !input.starts_with(first) {
+        return false;
+    }
+    let mut rest = &input[first.len()..];
+    let parts: Vec<&str> = parts.collect();
+    for (idx, part) in parts.iter().enumerate() {
+        if idx == parts.len() - 1 {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    rest.is_empty()
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Start a route for requests with `method`, whose url matches `pattern`.
+    ///
+    /// A `*` in the pattern matches anything. Patterns starting with `/` are matched against the
+    /// path and query of the url, other patterns against the whole url.
+    pub fn route(&self, method: Method, pattern: impl Into<String>) -> MockRoute<'_> {
+        MockRoute {
+            mock: self,
+            route: Route {
+                method,
+                pattern: pattern.into(),
+                headers: Vec::new(),
+                once: false,
+                respond: Box::new(|_| http::Response::new(Bytes::new())),
+            },
+        }
+    }
+
+    /// The requests received so far, oldest first.
+    pub fn requests(&self) -> Vec<http::Request<Body>> {
+        self.inner
+            .requests
+            .borrow()
+            .iter()
+            .map(clone_request)
+            .collect()
+    }
+
+    /// Forget the requests received so far.
+    pub fn clear_requests(&self) {
+        self.inner.requests.borrow_mut().clear();
+    }
+
+    fn respond(&self, request: &http::Request<Body>) -> Option<http::Response<Bytes>> {
+        let mut routes = self.inner.routes.borrow_mut();
+        let idx = routes.iter().position(|route| route.matches(request))?;
+        let response = (routes[idx].respond)(request);
+        if routes[idx].once {
+            routes.remove(idx);
+        }
+        Some(response)
+    }
+}
+
+impl fmt::Debug for MockTransport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MockTransport")
+            .field("routes", &self.inner.routes.borrow().len())
+            .field("requests", &self.inner.requests.borrow().len())
+            .finish()
+    }
+}
+
+impl Transport for MockTransport {
+    fn send(
+        &self,
+        request: http::Request<Body>,
+    ) -> LocalBoxFuture<'_, Result<Response, FetchError>> {
+        let response = self.respond(&request);
+        let err = || {
+            DomException::new(
+                DomExceptionKind::NotFound,
+                format!("no mock route for {} {}", request.method(), request.uri()),
+            )
+        };
+        let result = response.map(Response::from).ok_or_else(err);
+        self.inner.requests.borrow_mut().push(request);
+        future::ready(result.map_err(FetchError::from)).boxed_local()
+    }
+}
+
+/// A route being added to a `MockTransport`. It is added when one of the `respond` methods is
+/// called.
+pub struct MockRoute<'a> {
+    mock: &'a MockTransport,
+    route: Route,
+}
+
+impl<'a> MockRoute<'a> {
+    /// Only match requests that have a `name` header with this value.
+    pub fn header(mut self, name: HeaderName, value: impl Into<String>) -> Self {
+        self.route.headers.push((name, value.into()));
+        self
+    }
+
+    /// Only answer the first matching request, then remove the route.
+    pub fn once(mut self) -> Self {
+        self.route.once = true;
+        self
+    }
+
+    /// Answer with `status` and `body`.
+    pub fn respond(self, status: u16, body: impl Into<Bytes>) {
+        let body = body.into();
+        let status = expect!(
+            http::StatusCode::from_u16(status),
+            "making a mock response with status {}",
+            status
+        );
+        self.respond_with(move |_| {
+            let mut response = http::Response::new(body.clone());
+            *response.status_mut() = status;
+            response
+        })
+    }
+
+    /// Answer with the response returned by `f`.
+    pub fn respond_with(
+        mut self,
+        f: impl Fn(&http::Request<Body>) -> http::Response<Bytes> + 'static,
+    ) {
+        self.route.respond = Box::new(f);
+        self.mock.inner.routes.borrow_mut().push(self.route);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use http::header::AUTHORIZATION;
+
+    #[test]
+    fn glob() {
+        assert!(glob_matches("/users/*", "/users/1"));
+        assert!(glob_matches("/users/*/posts", "/users/1/posts"));
+        assert!(!glob_matches("/users/*/posts", "/users/1/comments"));
+        assert!(glob_matches("*", ""));
+        assert!(glob_matches("/users", "/users"));
+        assert!(!glob_matches("/users", "/users/1"));
+    }
+
+    #[test]
+    fn mock_transport() {
+        let mock = MockTransport::new();
+        mock.route(Method::GET, "/users/*")
+            .header(AUTHORIZATION, "Bearer secret")
+            .respond(200, "alice");
+        mock.route(Method::GET, "https://example.com/*")
+            .once()
+            .respond(404, "");
+        let request = |uri: &str, auth: Option<&'static str>| {
+            let mut request = http::Request::get(uri).body(Body::from("")).unwrap();
+            if let Some(auth) = auth {
+                let auth = http::header::HeaderValue::from_static(auth);
+                request.headers_mut().insert(AUTHORIZATION, auth);
+            }
+            request
+        };
+
+        let response = block_on(mock.send(request("/users/1", Some("Bearer secret")))).unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(block_on(response.text()).unwrap(), "alice");
+        // Reading a second time fails, like in the browser.
+        assert!(block_on(response.text()).is_err());
+
+        // Wrong header, then wrong method.
+        assert!(block_on(mock.send(request("/users/1", Some("Bearer wrong")))).is_err());
+        let post = http::Request::post("/users/1")
+            .body(Body::from(""))
+            .unwrap();
+        assert!(block_on(mock.send(post)).is_err());
+
+        let response = block_on(mock.send(request("https://example.com/", None))).unwrap();
+        assert_eq!(response.status(), 404);
+        match block_on(mock.send(request("https://example.com/", None))) {
+            Err(FetchError::Dom(err)) => assert_eq!(*err.kind(), DomExceptionKind::NotFound),
+            other => panic!("expected an error, got {:?}", other),
+        }
+
+        let uris: Vec<String> = mock
+            .requests()
+            .iter()
+            .map(|request| request.uri().to_string())
+            .collect();
+        assert_eq!(
+            uris,
+            [
+                "/users/1",
+                "/users/1",
+                "/users/1",
+                "https://example.com/",
+                "https://example.com/"
+            ]
+        );
+    }
+}
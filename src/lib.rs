@@ -1,19 +1,44 @@
 #[macro_use]
 #[doc(hidden)]
 pub mod macros;
+pub mod abort;
+pub mod animation;
+pub mod backend;
 pub mod dom_rect;
+pub mod error;
 pub mod event;
 pub mod node;
 pub mod element;
 pub mod fetch;
 pub mod document;
+pub mod time;
+pub mod window;
 //pub mod prelude;
 
+pub use animation::{animation_frame, next_frame};
+
+/// The document of the current page, from the backend chosen by `backend::current`.
+///
+/// Panics if there isn't one (e.g. in a web worker), see `try_document`.
 pub fn document() -> document::Document {
-    let window = expect_opt!(web_sys::window(), "window method failed - this is a bug!");
-    let document = expect_opt!(
-        window.document(),
-        "calling Window::document"
-    );
-    document::Document::from_web_sys(document)
+    expect_opt!(try_document(), "calling Backend::document")
+}
+
+/// The document of the current page, or `None` if there isn't one.
+pub fn try_document() -> Option<document::Document> {
+    backend::current()
+        .document()
+        .map(document::Document::from_handle)
+}
+
+/// The window of the current page. This is only available on the web.
+///
+/// Panics if there isn't one (e.g. in a web worker), see `try_window`.
+pub fn window() -> window::Window {
+    expect_opt!(try_window(), "calling web_sys::window")
+}
+
+/// The window of the current page, or `None` if there isn't one.
+pub fn try_window() -> Option<window::Window> {
+    web_sys::window().map(window::Window::from_web_sys)
 }
@@ -72,19 +72,6 @@ macro_rules! dict {
                     self
                 }
             )*
-
-            /// Convert into the corresponding web_sys type.
-            pub(crate) fn into_web_sys(&self) -> Option<web_sys::$name> {
-                if self == &<Self as Default>::default() {
-                    None
-                } else {
-                    let mut opts = web_sys::$name::new();
-                    $(if let Some(val) = self.$field {
-                        opts.$field(val);
-                    })*
-                    Some(opts)
-                }
-            }
         }
     };
     ($(#[$meta:meta])* $vis:vis struct $name:ident {
@@ -1,6 +1,6 @@
-use wasm_bindgen::prelude::*;
+use std::{convert::TryFrom, rc::Rc};
 
-use crate::{/*element, */event};
+use crate::{backend::Handle, error::DomException, event};
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum NodeType {
@@ -49,85 +49,119 @@ bitflags::bitflags! {
     }
 }
 
-#[repr(transparent)]
 #[derive(Debug, Clone)]
 pub struct Node {
-    pub(crate) inner: web_sys::Node,
+    target: event::EventTarget,
 }
 
 impl std::ops::Deref for Node {
     type Target = event::EventTarget;
 
     fn deref(&self) -> &Self::Target {
-        unsafe { std::mem::transmute::<&web_sys::EventTarget, &event::EventTarget>(&*self.inner) }
+        &self.target
+    }
+}
+
+impl From<Node> for event::EventTarget {
+    fn from(node: Node) -> event::EventTarget {
+        node.target
+    }
+}
+
+/// Gives the target back if it isn't a node.
+impl TryFrom<event::EventTarget> for Node {
+    type Error = event::EventTarget;
+
+    fn try_from(target: event::EventTarget) -> Result<Node, event::EventTarget> {
+        if target.raw.is_node() {
+            Ok(Node { target })
+        } else {
+            Err(target)
+        }
     }
 }
 
 impl Node {
-    /// Wrap a web_sys::Node
-    pub(crate) fn from_web_sys(inner: web_sys::Node) -> Node {
-        Node { inner }
+    /// Wrap a handle to a node from any backend.
+    pub fn from_handle(raw: Rc<dyn Handle>) -> Node {
+        Node {
+            target: event::EventTarget::from_handle(raw),
+        }
+    }
+
+    pub(crate) fn raw(&self) -> &dyn Handle {
+        &*self.target.raw
     }
 
-    // I believe this cannot error because we check the type of the parameter at compile-time.
+    /// Panics if `node` cannot be appended, for example because it is an ancestor of `self`. See
+    /// `try_append_child` for a version that doesn't panic.
     pub fn append_child(&self, node: &Node) {
-        expect!(
-            self.inner.append_child(&node.inner),
-            "calling Node::append_child"
-        );
+        expect!(self.try_append_child(node), "calling Node::append_child");
+    }
+    /// Fails with a `HierarchyRequestError` if `node` is an ancestor of `self`, or can't go
+    /// where it is being put (e.g. a second element in a document).
+    pub fn try_append_child(&self, node: &Node) -> Result<(), DomException> {
+        self.raw().append_child(node.raw())
     }
     // I believe this cannot error
     pub fn clone_node(&self, deep: bool) -> Node {
-        let node = expect!(
-            self.inner.clone_node_with_deep(deep),
-            "calling Node::clone_node"
-        );
-        Node::from_web_sys(node)
+        expect!(self.try_clone_node(deep), "calling Node::clone_node")
+    }
+    pub fn try_clone_node(&self, deep: bool) -> Result<Node, DomException> {
+        self.raw().clone_node(deep).map(Node::from_handle)
     }
     pub fn compare_document_position(&self, other: impl Into<Node>) -> DocumentPosition {
-        DocumentPosition::from_bits_truncate(
-            self.inner.compare_document_position(&other.into().inner),
-        )
+        self.raw().compare_document_position(other.into().raw())
     }
     pub fn contains(&self, other: impl Into<Node>) -> bool {
-        self.inner.contains(Some(&other.into().inner))
+        self.raw().contains(other.into().raw())
     }
     pub fn get_root_node(&self) -> Node {
-        Node::from_web_sys(self.inner.get_root_node())
+        Node::from_handle(self.raw().get_root_node())
     }
     pub fn has_child_nodes(&self) -> bool {
-        self.inner.has_child_nodes()
+        self.raw().has_child_nodes()
     }
+    /// Panics if `node` cannot be inserted. See `try_insert_before` for a version that doesn't
+    /// panic.
     pub fn insert_before(&self, node: impl Into<Node>, child: Option<impl Into<Node>>) {
         expect!(
-            self.inner.insert_before(
-                &node.into().inner,
-                child.map(Into::into).as_ref().map(|val| &val.inner),
-            ),
+            self.try_insert_before(node, child),
             "calling Node::insert_before"
         );
     }
+    /// Fails with a `HierarchyRequestError` if `node` can't go here, or a `NotFoundError` if
+    /// `child` is not a child of `self`.
+    pub fn try_insert_before(
+        &self,
+        node: impl Into<Node>,
+        child: Option<impl Into<Node>>,
+    ) -> Result<(), DomException> {
+        self.raw().insert_before(
+            node.into().raw(),
+            child.map(Into::into).as_ref().map(Node::raw),
+        )
+    }
     pub fn is_default_namespace(&self, namespace: Option<&str>) -> bool {
-        self.inner.is_default_namespace(namespace)
+        self.raw().is_default_namespace(namespace)
     }
     // todo can self ever be null - does node need to be optional?
     pub fn is_equal_node(&self, node: impl Into<Node>) -> bool {
-        self.inner.is_equal_node(Some(&node.into().inner))
+        self.raw().is_equal_node(node.into().raw())
     }
     // todo can self ever be null - does node need to be optional?
     pub fn is_same_node(&self, node: impl Into<Node>) -> bool {
-        self.inner.is_same_node(Some(&node.into().inner))
+        self.raw().is_same_node(node.into().raw())
     }
     pub fn lookup_namespace_uri(&self, prefix: Option<&str>) -> Option<String> {
-        self.inner.lookup_namespace_uri(prefix)
+        self.raw().lookup_namespace_uri(prefix)
     }
     pub fn lookup_prefix(&self, namespace: Option<&str>) -> Option<String> {
-        self.inner.lookup_prefix(namespace)
+        self.raw().lookup_prefix(namespace)
     }
     pub fn normalize(&self) {
-        self.inner.normalize()
+        self.raw().normalize()
     }
-    // todo type error
     // The method throws an exception in 2 different ways:
     //
     // If the child was in fact a child of element and so existing on the DOM,
@@ -139,38 +173,39 @@ impl Node {
     // the method throws the following exception:
     // `Uncaught TypeError: Failed to execute 'removeChild' on 'Node':
     // parameter 1 is not of type 'Node'.`
-    pub fn remove_child(&self, child: impl Into<Node>) -> Result<Node, JsValue> {
-        self.inner.remove_child(&child.into().inner).map(Node::from_web_sys)
+    pub fn remove_child(&self, child: impl Into<Node>) -> Result<Node, DomException> {
+        self.raw()
+            .remove_child(child.into().raw())
+            .map(Node::from_handle)
     }
-    // todo type error & do we need to return parameter?
+    // todo do we need to return parameter?
     pub fn replace_child(
         &self,
         node: impl Into<Node>,
         child: impl Into<Node>,
-    ) -> Result<Node, JsValue> {
-        self.inner
-            .replace_child(&node.into().inner, &child.into().inner)
-            .map(Node::from_web_sys)
+    ) -> Result<Node, DomException> {
+        self.raw()
+            .replace_child(node.into().raw(), child.into().raw())
+            .map(Node::from_handle)
     }
     pub fn node_type(&self) -> NodeType {
-        NodeType::from_web_sys(self.inner.node_type())
+        self.raw().node_type()
     }
     pub fn node_name(&self) -> String {
-        self.inner.node_name()
+        self.raw().node_name()
     }
-    // todo type error
-    pub fn base_uri(&self) -> Result<Option<String>, JsValue> {
-        self.inner.base_uri()
+    pub fn base_uri(&self) -> Result<Option<String>, DomException> {
+        self.raw().base_uri()
     }
     pub fn is_connected(&self) -> bool {
-        self.inner.is_connected()
+        self.raw().is_connected()
     }
     //pub fn owner_document(&self) -> Option<Document> {
     //unimplemented!()
 
     //}
     pub fn parent_node(&self) -> Option<Node> {
-        self.inner.parent_node().map(Node::from_web_sys)
+        self.raw().parent_node().map(Node::from_handle)
     }
     /*pub fn parent_element(&self) -> Option<element::Element> {
         self.inner
@@ -178,49 +213,51 @@ impl Node {
             .map(element::Element::from_web_sys)
     }*/
     pub fn first_child(&self) -> Option<Node> {
-        self.inner.first_child().map(Node::from_web_sys)
+        self.raw().first_child().map(Node::from_handle)
     }
     pub fn last_child(&self) -> Option<Node> {
-        self.inner.last_child().map(Node::from_web_sys)
+        self.raw().last_child().map(Node::from_handle)
     }
     pub fn previous_sibling(&self) -> Option<Node> {
-        self.inner.previous_sibling().map(Node::from_web_sys)
+        self.raw().previous_sibling().map(Node::from_handle)
     }
     pub fn next_sibling(&self) -> Option<Node> {
-        self.inner.next_sibling().map(Node::from_web_sys)
+        self.raw().next_sibling().map(Node::from_handle)
     }
     pub fn node_value(&self) -> Option<String> {
-        self.inner.node_value()
+        self.raw().node_value()
     }
     pub fn set_node_value(&self, node_value: Option<&str>) {
-        self.inner.set_node_value(node_value)
+        self.raw().set_node_value(node_value)
     }
     pub fn text_content(&self) -> Option<String> {
-        self.inner.text_content()
+        self.raw().text_content()
     }
     pub fn set_text_content(&self, text_content: Option<&str>) {
-        self.inner.set_text_content(text_content)
+        self.raw().set_text_content(text_content)
     }
 }
 
 pub struct NodeList {
-    inner: web_sys::NodeList,
+    nodes: Vec<Node>,
 }
 
 impl NodeList {
-    pub(crate) fn from_web_sys(inner: web_sys::NodeList) -> Self {
-        NodeList { inner }
+    pub(crate) fn from_handles(handles: Vec<Rc<dyn Handle>>) -> Self {
+        NodeList {
+            nodes: handles.into_iter().map(Node::from_handle).collect(),
+        }
     }
 }
 
 impl NodeList {
     /// The number of nodes in this collection
     pub fn len(&self) -> usize {
-        self.inner.length() as usize
+        self.nodes.len()
     }
 
     pub fn get(&self, idx: usize) -> Option<Node> {
-        self.inner.get(idx as u32).map(|inner| Node { inner })
+        self.nodes.get(idx).cloned()
     }
 }
 
@@ -229,32 +266,37 @@ impl IntoIterator for NodeList {
     type IntoIter = NodeListIterator;
     fn into_iter(self) -> Self::IntoIter {
         NodeListIterator {
-            idx: 0,
-            len: self.inner.length() as usize,
-            inner: self.inner,
+            inner: self.nodes.into_iter(),
         }
     }
 }
 
 pub struct NodeListIterator {
-    idx: usize,
-    len: usize,
-    inner: web_sys::NodeList,
+    inner: std::vec::IntoIter<Node>,
 }
 
 impl Iterator for NodeListIterator {
     type Item = Node;
     fn next(&mut self) -> Option<Self::Item> {
-        if self.idx >= self.len {
-            return None;
-        }
-        let inner = expect_opt!(
-            self.inner.get(self.idx as u32),
-            "out of bounds error indexing into NodeList"
-        );
-        self.idx += 1;
-        Some(Node { inner })
+        self.inner.next()
     }
 }
 
 impl std::iter::FusedIterator for NodeListIterator {}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::DomExceptionKind;
+
+    #[test]
+    fn try_append_child() {
+        let document = crate::backend::memory::MemoryBackend.new_document();
+        let document = crate::document::Document::from_handle(document);
+        let body = document.try_body().unwrap();
+        let div = document.create_element("div");
+        body.append_child(&div);
+        let err = div.try_append_child(&body).unwrap_err();
+        assert_eq!(*err.kind(), DomExceptionKind::HierarchyRequest);
+        assert!(document.try_create_element("not valid").is_err());
+    }
+}
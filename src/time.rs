@@ -0,0 +1,309 @@
+//! Timers, using `setTimeout`, `setInterval` and `requestIdleCallback`.
+//!
+//! These only work on the web. Timers work in workers as well as windows, but idle callbacks need
+//! a window. Every timer is cancelled when its guard is dropped.
+
+use futures::channel::oneshot;
+use std::{
+    cell::{Cell, RefCell},
+    convert::TryFrom,
+    error::Error,
+    fmt,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+    time::Duration,
+};
+use wasm_bindgen::{prelude::*, JsCast};
+
+use crate::event::SubscribeGuard;
+
+#[wasm_bindgen]
+extern "C" {
+    // The globals, rather than the `Window` methods, so timers work in workers too.
+    #[wasm_bindgen(js_name = setTimeout)]
+    fn set_timeout(handler: &js_sys::Function, timeout: i32) -> JsValue;
+    #[wasm_bindgen(js_name = clearTimeout)]
+    fn clear_timeout(id: &JsValue);
+    #[wasm_bindgen(js_name = setInterval)]
+    fn set_interval(handler: &js_sys::Function, timeout: i32) -> JsValue;
+    #[wasm_bindgen(js_name = clearInterval)]
+    fn clear_interval(id: &JsValue);
+}
+
+/// The delay in milliseconds, saturating at the largest delay the browser accepts.
+fn millis(duration: Duration) -> i32 {
+    i32::try_from(duration.as_millis()).unwrap_or(i32::MAX)
+}
+
+/// Calls a function once after a delay, unless it is dropped first.
+pub struct Timeout {
+    id: JsValue,
+    closure: Option<Closure<dyn FnMut()>>,
+}
+
+impl Timeout {
+    /// Call `callback` after `delay`.
+    pub fn new(delay: Duration, callback: impl FnOnce() + 'static) -> Timeout {
+        let closure = Closure::once(callback);
+        let id = set_timeout(closure.as_ref().unchecked_ref(), millis(delay));
+        Timeout {
+            id,
+            closure: Some(closure),
+        }
+    }
+
+    /// Cancel the timeout. The same as dropping it.
+    pub fn cancel(self) {}
+
+    /// Drop the guard without cancelling the timeout.
+    pub fn forget(mut self) {
+        // The callback will still be called, so it has to be leaked.
+        std::mem::forget(self.closure.take());
+    }
+}
+
+impl fmt::Debug for Timeout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Timeout").field("id", &self.id).finish()
+    }
+}
+
+impl Drop for Timeout {
+    fn drop(&mut self) {
+        if self.closure.is_some() {
+            // Clearing a timeout that has already fired does nothing.
+            clear_timeout(&self.id);
+        }
+    }
+}
+
+/// Calls a function repeatedly, with a delay between each call, until it is dropped.
+pub struct Interval {
+    id: JsValue,
+    closure: Option<Closure<dyn FnMut()>>,
+}
+
+impl Interval {
+    /// Call `callback` every `period`, starting one `period` from now.
+    pub fn new(period: Duration, callback: impl FnMut() + 'static) -> Interval {
+        let closure = Closure::wrap(Box::new(callback) as Box<dyn FnMut()>);
+        let id = set_interval(closure.as_ref().unchecked_ref(), millis(period));
+        Interval {
+            id,
+            closure: Some(closure),
+        }
+    }
+
+    /// Stop the interval. The same as dropping it.
+    pub fn cancel(self) {}
+
+    /// Drop the guard without stopping the interval, which then runs forever.
+    pub fn forget(mut self) {
+        std::mem::forget(self.closure.take());
+    }
+}
+
+impl fmt::Debug for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Interval").field("id", &self.id).finish()
+    }
+}
+
+impl Drop for Interval {
+    fn drop(&mut self) {
+        if self.closure.is_some() {
+            clear_interval(&self.id);
+        }
+    }
+}
+
+/// A future that finishes after `delay`.
+pub fn sleep(delay: Duration) -> Sleep {
+    let (sender, receiver) = oneshot::channel();
+    let timeout = Timeout::new(delay, move || {
+        let _ = sender.send(());
+    });
+    Sleep {
+        receiver,
+        _timeout: timeout,
+    }
+}
+
+/// Finishes after a delay, from `sleep`.
+///
+/// Dropping the future first cancels the timer.
+#[derive(Debug)]
+pub struct Sleep {
+    receiver: oneshot::Receiver<()>,
+    _timeout: Timeout,
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        match Pin::new(&mut self.receiver).poll(cx) {
+            Poll::Ready(Ok(())) => Poll::Ready(()),
+            // The timer is kept until it fires, so the sender can't be dropped first.
+            Poll::Ready(Err(_)) | Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Run `future`, giving up with `Elapsed` if it hasn't finished after `limit`.
+pub fn timeout<F: Future>(future: F, limit: Duration) -> WithTimeout<F> {
+    WithTimeout {
+        future: Box::pin(future),
+        sleep: sleep(limit),
+    }
+}
+
+/// A future with a time limit, from `timeout`.
+///
+/// Dropping it drops the inner future and cancels the timer.
+pub struct WithTimeout<F> {
+    future: Pin<Box<F>>,
+    sleep: Sleep,
+}
+
+impl<F> fmt::Debug for WithTimeout<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("WithTimeout")
+            .field("sleep", &self.sleep)
+            .finish()
+    }
+}
+
+impl<F: Future> Future for WithTimeout<F> {
+    type Output = Result<F::Output, Elapsed>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        if let Poll::Ready(output) = self.future.as_mut().poll(cx) {
+            return Poll::Ready(Ok(output));
+        }
+        match Pin::new(&mut self.sleep).poll(cx) {
+            Poll::Ready(()) => Poll::Ready(Err(Elapsed)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// The error from `timeout` when the future took too long.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Elapsed;
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("the time limit was reached")
+    }
+}
+
+impl Error for Elapsed {}
+
+/// Wrap `callback` so it only runs once calls have stopped for `delay`, with the argument of the
+/// last call. Handy for search boxes, where only the final input matters.
+///
+/// A pending call is cancelled when the returned function is dropped.
+pub fn debounce<T: 'static>(delay: Duration, callback: impl Fn(T) + 'static) -> impl Fn(T) {
+    let callback = Rc::new(callback);
+    let pending = RefCell::new(None);
+    move |arg| {
+        let callback = callback.clone();
+        // Replacing the timeout cancels the previous call.
+        *pending.borrow_mut() = Some(Timeout::new(delay, move || callback(arg)));
+    }
+}
+
+/// Wrap `callback` so it runs at most once every `period`. The first call runs straight away,
+/// and calls made during the following `period` are dropped.
+pub fn throttle<T>(period: Duration, callback: impl Fn(T)) -> impl Fn(T) {
+    let waiting = Rc::new(Cell::new(false));
+    let cooldown = RefCell::new(None);
+    move |arg| {
+        if waiting.get() {
+            return;
+        }
+        waiting.set(true);
+        let waiting = waiting.clone();
+        *cooldown.borrow_mut() = Some(Timeout::new(period, move || waiting.set(false)));
+        callback(arg);
+    }
+}
+
+/// Call `callback` when the browser is idle, for work that can wait. Dropping the guard first
+/// cancels it.
+pub fn request_idle_callback(callback: impl FnOnce(IdleDeadline) + 'static) -> SubscribeGuard {
+    request_idle(callback, None)
+}
+
+/// Like `request_idle_callback`, but `callback` is called after `limit` even if the browser hasn't
+/// been idle, in which case `IdleDeadline::did_timeout` is `true`.
+pub fn request_idle_callback_with_timeout(
+    limit: Duration,
+    callback: impl FnOnce(IdleDeadline) + 'static,
+) -> SubscribeGuard {
+    request_idle(callback, Some(limit))
+}
+
+fn request_idle(
+    callback: impl FnOnce(IdleDeadline) + 'static,
+    limit: Option<Duration>,
+) -> SubscribeGuard {
+    let window = crate::window();
+    let closure =
+        Closure::once(move |inner: web_sys::IdleDeadline| callback(IdleDeadline { inner }));
+    let function = closure.as_ref().unchecked_ref();
+    let id = match limit {
+        Some(limit) => {
+            let mut options = web_sys::IdleRequestOptions::new();
+            options.timeout(u32::try_from(limit.as_millis()).unwrap_or(u32::MAX));
+            window
+                .as_web_sys()
+                .request_idle_callback_with_options(function, &options)
+        }
+        None => window.as_web_sys().request_idle_callback(function),
+    };
+    let id = expect!(id, "calling Window::request_idle_callback");
+    let mut closure = Some(closure);
+    SubscribeGuard::new(move || {
+        window.as_web_sys().cancel_idle_callback(id);
+        closure.take();
+    })
+}
+
+/// How long an idle callback can run before it holds up the browser.
+#[derive(Debug, Clone)]
+pub struct IdleDeadline {
+    inner: web_sys::IdleDeadline,
+}
+
+impl IdleDeadline {
+    /// How much idle time is left, which is zero once it has run out.
+    pub fn time_remaining(&self) -> Duration {
+        Duration::from_secs_f64(self.inner.time_remaining().max(0.) / 1000.)
+    }
+
+    /// Whether the callback is being run because its timeout passed, rather than because the
+    /// browser is idle.
+    pub fn did_timeout(&self) -> bool {
+        self.inner.did_timeout()
+    }
+
+    /// The underlying `web_sys` deadline.
+    pub fn as_web_sys(&self) -> &web_sys::IdleDeadline {
+        &self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn millis_saturates() {
+        assert_eq!(millis(Duration::from_micros(2500)), 2);
+        assert_eq!(millis(Duration::from_secs(u64::MAX)), i32::MAX);
+    }
+}
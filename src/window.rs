@@ -0,0 +1,265 @@
+//! The browser window, for the viewport size, scrolling, dialogs and visibility.
+//!
+//! Like `abort` and `fetch`, this talks to the browser directly, so it only works on the web.
+
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    backend::web::WebHandle,
+    document::Document,
+    error::DomException,
+    event::{AddEventListenerOptions, EventKind, EventTarget, SubscribeGuard},
+};
+
+/// A browser window. Derefs to `EventTarget`, for `resize` and `scroll` events and the like.
+#[derive(Debug, Clone)]
+pub struct Window {
+    inner: web_sys::Window,
+    target: EventTarget,
+}
+
+impl std::ops::Deref for Window {
+    type Target = EventTarget;
+
+    fn deref(&self) -> &Self::Target {
+        &self.target
+    }
+}
+
+impl From<Window> for EventTarget {
+    fn from(window: Window) -> EventTarget {
+        window.target
+    }
+}
+
+impl Window {
+    pub(crate) fn from_web_sys(inner: web_sys::Window) -> Self {
+        let target = EventTarget::from_handle(WebHandle::wrap(inner.clone()));
+        Window { inner, target }
+    }
+
+    /// The document loaded in this window, if there is one.
+    pub fn document(&self) -> Option<Document> {
+        self.inner
+            .document()
+            .map(|document| Document::from_handle(WebHandle::wrap(document)))
+    }
+
+    /// The width of the viewport in CSS pixels, including the vertical scrollbar.
+    pub fn inner_width(&self) -> f64 {
+        let width = expect!(self.inner.inner_width(), "calling Window::inner_width");
+        expect_opt!(width.as_f64(), "innerWidth was not a number")
+    }
+
+    /// The height of the viewport in CSS pixels, including the horizontal scrollbar.
+    pub fn inner_height(&self) -> f64 {
+        let height = expect!(self.inner.inner_height(), "calling Window::inner_height");
+        expect_opt!(height.as_f64(), "innerHeight was not a number")
+    }
+
+    /// The number of device pixels per CSS pixel. Changes when the page is zoomed or moved to
+    /// another screen, see `watch_device_pixel_ratio`.
+    pub fn device_pixel_ratio(&self) -> f64 {
+        self.inner.device_pixel_ratio()
+    }
+
+    /// Call `watcher` with the new device pixel ratio whenever it changes, until the guard is
+    /// dropped.
+    ///
+    /// There is no event for this, so it is done by watching a media query that matches the
+    /// current ratio, and making a new one each time it stops matching.
+    pub fn watch_device_pixel_ratio(&self, watcher: impl Fn(f64) + 'static) -> SubscribeGuard {
+        let slot = Rc::new(RefCell::new(None));
+        watch_ratio(self.clone(), Rc::new(watcher), &slot);
+        SubscribeGuard::new(move || {
+            let guard = slot.borrow_mut().take();
+            drop(guard);
+        })
+    }
+
+    /// How far the document has been scrolled horizontally, in CSS pixels.
+    pub fn scroll_x(&self) -> f64 {
+        expect!(self.inner.scroll_x(), "calling Window::scroll_x")
+    }
+
+    /// How far the document has been scrolled vertically, in CSS pixels.
+    pub fn scroll_y(&self) -> f64 {
+        expect!(self.inner.scroll_y(), "calling Window::scroll_y")
+    }
+
+    /// Jump to the given position in the document.
+    pub fn scroll_to(&self, x: f64, y: f64) {
+        self.inner.scroll_to_with_x_and_y(x, y)
+    }
+
+    /// Scroll the document, which can be done smoothly by setting `behavior`.
+    pub fn scroll_to_opts(&self, options: &ScrollToOptions) {
+        self.inner
+            .scroll_to_with_scroll_to_options(&options.into_web_sys())
+    }
+
+    /// Panics if the window can't be opened, see `try_open`.
+    pub fn open(&self, url: &str, target: &str) -> Option<Window> {
+        expect!(self.try_open(url, target), "calling Window::open")
+    }
+
+    /// Load `url` into the browsing context called `target` (e.g. `"_blank"` for a new tab).
+    ///
+    /// Gives `None` if a popup blocker stopped it. Fails if `url` can't be parsed.
+    pub fn try_open(&self, url: &str, target: &str) -> Result<Option<Window>, DomException> {
+        self.inner
+            .open_with_url_and_target(url, target)
+            .map(|window| window.map(Window::from_web_sys))
+            .map_err(DomException::from_js)
+    }
+
+    /// Show `message` and wait for the user to dismiss it.
+    pub fn alert(&self, message: &str) {
+        expect!(
+            self.inner.alert_with_message(message),
+            "calling Window::alert"
+        )
+    }
+
+    /// Show `message` and wait for the user to choose OK (`true`) or Cancel (`false`).
+    pub fn confirm(&self, message: &str) -> bool {
+        expect!(
+            self.inner.confirm_with_message(message),
+            "calling Window::confirm"
+        )
+    }
+
+    /// Ask the user for some text, starting with `default`. Gives `None` if they cancel.
+    pub fn prompt(&self, message: &str, default: &str) -> Option<String> {
+        expect!(
+            self.inner.prompt_with_message_and_default(message, default),
+            "calling Window::prompt"
+        )
+    }
+
+    /// Whether the document can be seen, for example to pause work while the tab is in the
+    /// background. Listen for `EventKind::VisibilityChange` on the document to know when it
+    /// changes.
+    pub fn visibility_state(&self) -> VisibilityState {
+        match self
+            .inner
+            .document()
+            .map(|document| document.visibility_state())
+        {
+            Some(web_sys::VisibilityState::Visible) => VisibilityState::Visible,
+            _ => VisibilityState::Hidden,
+        }
+    }
+
+    /// The underlying `web_sys` window.
+    pub fn as_web_sys(&self) -> &web_sys::Window {
+        &self.inner
+    }
+}
+
+/// Call `watcher` the next time the device pixel ratio changes, and keep watching after that.
+fn watch_ratio(
+    window: Window,
+    watcher: Rc<dyn Fn(f64)>,
+    slot: &Rc<RefCell<Option<SubscribeGuard>>>,
+) {
+    let query = format!("(resolution: {}dppx)", window.device_pixel_ratio());
+    let list = match expect!(
+        window.inner.match_media(&query),
+        "calling Window::match_media"
+    ) {
+        Some(list) => list,
+        None => return,
+    };
+    let list = EventTarget::from_handle(WebHandle::wrap(list));
+    let weak = Rc::downgrade(slot);
+    let mut options = AddEventListenerOptions::default();
+    options.once(true);
+    let guard = list.add_event_listener_opts(
+        EventKind::Change,
+        move |_| {
+            if let Some(slot) = weak.upgrade() {
+                watcher(window.device_pixel_ratio());
+                watch_ratio(window.clone(), watcher.clone(), &slot);
+            }
+        },
+        options,
+    );
+    *slot.borrow_mut() = Some(guard);
+}
+
+/// Whether a document can be seen by the user.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum VisibilityState {
+    Visible,
+    /// In a background tab, minimized, or the screen is locked.
+    Hidden,
+}
+
+/// How a scroll should move.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ScrollBehavior {
+    /// Whatever the `scroll-behavior` CSS property says.
+    Auto,
+    /// Jump straight there.
+    Instant,
+    /// Animate the scroll.
+    Smooth,
+}
+
+impl Default for ScrollBehavior {
+    fn default() -> Self {
+        ScrollBehavior::Auto
+    }
+}
+
+impl ScrollBehavior {
+    fn into_web_sys(self) -> web_sys::ScrollBehavior {
+        match self {
+            ScrollBehavior::Auto => web_sys::ScrollBehavior::Auto,
+            ScrollBehavior::Instant => web_sys::ScrollBehavior::Instant,
+            ScrollBehavior::Smooth => web_sys::ScrollBehavior::Smooth,
+        }
+    }
+}
+
+/// Options for the `Window::scroll_to_opts` function. Coordinates that are left out stay where
+/// they are.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct ScrollToOptions {
+    pub left: Option<f64>,
+    pub top: Option<f64>,
+    pub behavior: Option<ScrollBehavior>,
+}
+
+impl ScrollToOptions {
+    pub fn left(&mut self, val: f64) -> &mut Self {
+        self.left = Some(val);
+        self
+    }
+
+    pub fn top(&mut self, val: f64) -> &mut Self {
+        self.top = Some(val);
+        self
+    }
+
+    pub fn behavior(&mut self, val: ScrollBehavior) -> &mut Self {
+        self.behavior = Some(val);
+        self
+    }
+
+    /// Convert into the corresponding web_sys type.
+    fn into_web_sys(self) -> web_sys::ScrollToOptions {
+        let mut opts = web_sys::ScrollToOptions::new();
+        if let Some(left) = self.left {
+            opts.left(left);
+        }
+        if let Some(top) = self.top {
+            opts.top(top);
+        }
+        if let Some(behavior) = self.behavior {
+            opts.behavior(behavior.into_web_sys());
+        }
+        opts
+    }
+}